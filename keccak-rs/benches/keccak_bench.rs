@@ -1,5 +1,8 @@
+// Benchmarks index lanes and rounds explicitly to mirror the specification
+#![allow(clippy::needless_range_loop)]
+
 use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main, measurement::WallTime};
-use keccak_rs::{sha3_256, sha3_224, sha3_384, sha3_512, KeccakF1600, RoundConstantMode};
+use keccak_rs::{sha3_256, sha3_224, sha3_384, sha3_512, shake128, turboshake128, KeccakF1600, RoundConstantMode};
 use std::hint::black_box;
 
 /// Benchmark the raw Keccak-f[1600] permutation
@@ -193,6 +196,30 @@ fn bench_sha3_all_variants(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark the 12-round TurboSHAKE128 against the full 24-round SHAKE128
+fn bench_turboshake_vs_shake(c: &mut Criterion) {
+    let mut group = c.benchmark_group("turboshake_vs_shake");
+
+    let data = vec![0xA5u8; 16384];
+    group.throughput(Throughput::Bytes(data.len() as u64));
+
+    group.bench_function("shake128", |b| {
+        b.iter(|| {
+            let out = shake128(black_box(&data), 32);
+            black_box(out);
+        });
+    });
+
+    group.bench_function("turboshake128", |b| {
+        b.iter(|| {
+            let out = turboshake128(black_box(&data), 0x1F, 32);
+            black_box(out);
+        });
+    });
+
+    group.finish();
+}
+
 /// Benchmark round constant generation methods
 fn bench_round_constants(c: &mut Criterion) {
     use keccak_rs::round_constants::{get_round_constant, lfsr_round_constant, RoundConstantMode, RC_TABLE};
@@ -254,6 +281,7 @@ criterion_group!(
         bench_sha3_256_throughput,
         bench_sha3_variants,
         bench_sha3_all_variants,
+        bench_turboshake_vs_shake,
         bench_round_constants
 );
 
@@ -0,0 +1,128 @@
+use alloc::vec::Vec;
+use crate::encoding::{bytepad, encode_string};
+use crate::{Keccak, XofReader};
+
+/// Builds the sponge for cSHAKE with the given rate, function name N and customization S.
+///
+/// With both strings empty cSHAKE is defined to be plain SHAKE, otherwise the
+/// encoded strings are absorbed as a full prefix block and the 00 suffix is used.
+pub(crate) fn cshake_sponge(rate: usize, function_name: &[u8], customization: &[u8]) -> Keccak {
+    if function_name.is_empty() && customization.is_empty() {
+        return Keccak::new(rate, 0x1F);
+    }
+
+    let mut sponge = Keccak::new(rate, 0x04);
+    let mut prefix = encode_string(function_name);
+    prefix.extend_from_slice(&encode_string(customization));
+    sponge.update(&bytepad(&prefix, rate));
+    sponge
+}
+
+macro_rules! cshake_hasher {
+    ($(#[$doc:meta])* $name:ident, $rate:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Keccak,
+        }
+
+        impl $name {
+            pub fn new(function_name: &[u8], customization: &[u8]) -> Self {
+                $name {
+                    sponge: cshake_sponge($rate, function_name, customization),
+                }
+            }
+
+            pub fn update(&mut self, input: &[u8]) {
+                self.sponge.update(input);
+            }
+
+            pub fn finalize(self, output_len: usize) -> Vec<u8> {
+                self.sponge.finalize(output_len)
+            }
+
+            pub fn finalize_xof(self) -> XofReader {
+                self.sponge.finalize_xof()
+            }
+        }
+    };
+}
+
+cshake_hasher!(
+    /// Incremental cSHAKE128 with function name N and customization string S
+    CShake128, 168
+);
+cshake_hasher!(
+    /// Incremental cSHAKE256 with function name N and customization string S
+    CShake256, 136
+);
+
+pub fn cshake128(data: &[u8], output_len: usize, function_name: &[u8], customization: &[u8]) -> Vec<u8> {
+    let mut hasher = CShake128::new(function_name, customization);
+    hasher.update(data);
+    hasher.finalize(output_len)
+}
+
+pub fn cshake256(data: &[u8], output_len: usize, function_name: &[u8], customization: &[u8]) -> Vec<u8> {
+    let mut hasher = CShake256::new(function_name, customization);
+    hasher.update(data);
+    hasher.finalize(output_len)
+}
+
+// cSHAKE samples from the NIST SP 800-185 example values
+
+#[test]
+fn test_cshake128_sample_1() {
+    let out = cshake128(&[0x00, 0x01, 0x02, 0x03], 32, b"", b"Email Signature");
+    assert_eq!(
+        hex::encode(out),
+        "c1c36925b6409a04f1b504fcbca9d82b4017277cb5ed2b2065fc1d3814d5aaf5"
+    );
+}
+
+#[test]
+fn test_cshake128_sample_2() {
+    let data: Vec<u8> = (0..200).map(|i| i as u8).collect();
+    let out = cshake128(&data, 32, b"", b"Email Signature");
+    assert_eq!(
+        hex::encode(out),
+        "c5221d50e4f822d96a2e8881a961420f294b7b24fe3d2094baed2c6524cc166b"
+    );
+}
+
+#[test]
+fn test_cshake256_sample_3() {
+    let out = cshake256(&[0x00, 0x01, 0x02, 0x03], 64, b"", b"Email Signature");
+    assert_eq!(
+        hex::encode(out),
+        "d008828e2b80ac9d2218ffee1d070c48b8e4c87bff32c9699d5b6896eee0edd164020e2be0560858d9c00c037e34a96937c561a74c412bb4c746469527281c8c"
+    );
+}
+
+#[test]
+fn test_cshake256_sample_4() {
+    let data: Vec<u8> = (0..200).map(|i| i as u8).collect();
+    let out = cshake256(&data, 64, b"", b"Email Signature");
+    assert_eq!(
+        hex::encode(out),
+        "07dc27b11e51fbac75bc7b3c1d983e8b4b85fb1defaf218912ac86430273091727f42b17ed1df63e8ec118f04b23633c1dfb1574c8fb55cb45da8e25afb092bb"
+    );
+}
+
+#[test]
+fn test_cshake_empty_strings_is_shake() {
+    use crate::{shake128, shake256};
+
+    assert_eq!(cshake128(b"abc", 200, b"", b""), shake128(b"abc", 200));
+    assert_eq!(cshake256(b"abc", 200, b"", b""), shake256(b"abc", 200));
+}
+
+#[test]
+fn test_cshake_function_name() {
+    // Only N set still selects the cSHAKE padding
+    let out = cshake128(b"abc", 32, b"KMAC", b"");
+    assert_eq!(
+        hex::encode(out),
+        "80b57b58ecfc9733aebe9cb050e7ceb2f97a78dddde4caa729507f60edb15632"
+    );
+}
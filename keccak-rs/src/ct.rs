@@ -0,0 +1,47 @@
+use core::sync::atomic::{compiler_fence, Ordering};
+
+/// Compares two byte strings without an early exit on the first mismatch.
+///
+/// Use it instead of `==` whenever one side is secret or attacker-controlled,
+/// such as a digest or MAC tag being checked. Only the contents are protected:
+/// slices of different lengths compare unequal immediately, since tag lengths
+/// are public.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    core::hint::black_box(diff) == 0
+}
+
+/// Overwrites `values` with zeros using volatile writes, so the stores are
+/// not removed as dead even when the memory is freed right afterwards
+pub(crate) fn zeroize<T: Copy + Default>(values: &mut [T]) {
+    for value in values.iter_mut() {
+        // SAFETY: `value` is a valid, aligned and exclusive reference
+        unsafe { core::ptr::write_volatile(value, T::default()) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+#[test]
+fn test_constant_time_eq() {
+    assert!(constant_time_eq(b"", b""));
+    assert!(constant_time_eq(b"abc", b"abc"));
+    assert!(!constant_time_eq(b"abc", b"abd"));
+    assert!(!constant_time_eq(b"abc", b"ab"));
+}
+
+#[test]
+fn test_zeroize() {
+    let mut lanes = [u64::MAX; 25];
+    zeroize(&mut lanes);
+    assert_eq!(lanes, [0; 25]);
+
+    let mut bytes = *b"secret";
+    zeroize(&mut bytes);
+    assert_eq!(bytes, [0; 6]);
+}
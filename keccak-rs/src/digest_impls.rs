@@ -0,0 +1,232 @@
+//! RustCrypto `digest` 0.10 trait implementations, so the hashers can be used
+//! wherever generic code asks for `D: Digest` (HMAC, HKDF, signatures, ...).
+//!
+//! The block size reported through `BlockSizeUser` is the sponge rate, which is
+//! what HMAC-SHA3 (FIPS 198-1 with SHA-3) pads the key to.
+
+use digest::consts::{U104, U136, U144, U168, U28, U32, U48, U64, U72};
+use digest::crypto_common::BlockSizeUser;
+use digest::{
+    ExtendableOutput, ExtendableOutputReset, FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset,
+    Update,
+};
+
+use crate::{
+    Keccak224, Keccak256, Keccak384, Keccak512, RawShake128, RawShake256, Sha3_224, Sha3_256, Sha3_384, Sha3_512,
+    Shake128, Shake256, XofReader,
+};
+
+macro_rules! impl_fixed_digest {
+    ($name:ident, $output_size:ty, $block_size:ty) => {
+        impl HashMarker for $name {}
+
+        impl BlockSizeUser for $name {
+            type BlockSize = $block_size;
+        }
+
+        impl OutputSizeUser for $name {
+            type OutputSize = $output_size;
+        }
+
+        impl Update for $name {
+            fn update(&mut self, data: &[u8]) {
+                self.sponge.update(data);
+            }
+        }
+
+        impl FixedOutput for $name {
+            fn finalize_into(self, out: &mut Output<Self>) {
+                self.sponge.finalize_into(out);
+            }
+        }
+
+        impl FixedOutputReset for $name {
+            fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                self.sponge.finalize_reset(out);
+            }
+        }
+
+        impl Reset for $name {
+            fn reset(&mut self) {
+                self.sponge.reset();
+            }
+        }
+    };
+}
+
+macro_rules! impl_xof_digest {
+    ($name:ident, $block_size:ty) => {
+        impl HashMarker for $name {}
+
+        impl BlockSizeUser for $name {
+            type BlockSize = $block_size;
+        }
+
+        impl Update for $name {
+            fn update(&mut self, data: &[u8]) {
+                self.sponge.update(data);
+            }
+        }
+
+        impl ExtendableOutput for $name {
+            type Reader = XofReader;
+
+            fn finalize_xof(self) -> XofReader {
+                self.sponge.finalize_xof()
+            }
+        }
+
+        impl ExtendableOutputReset for $name {
+            fn finalize_xof_reset(&mut self) -> XofReader {
+                core::mem::take(self).sponge.finalize_xof()
+            }
+        }
+
+        impl Reset for $name {
+            fn reset(&mut self) {
+                self.sponge.reset();
+            }
+        }
+    };
+}
+
+impl_fixed_digest!(Sha3_224, U28, U144);
+impl_fixed_digest!(Sha3_256, U32, U136);
+impl_fixed_digest!(Sha3_384, U48, U104);
+impl_fixed_digest!(Sha3_512, U64, U72);
+impl_fixed_digest!(Keccak224, U28, U144);
+impl_fixed_digest!(Keccak256, U32, U136);
+impl_fixed_digest!(Keccak384, U48, U104);
+impl_fixed_digest!(Keccak512, U64, U72);
+
+impl_xof_digest!(Shake128, U168);
+impl_xof_digest!(Shake256, U136);
+impl_xof_digest!(RawShake128, U168);
+impl_xof_digest!(RawShake256, U136);
+
+impl digest::XofReader for XofReader {
+    fn read(&mut self, buffer: &mut [u8]) {
+        XofReader::read(self, buffer);
+    }
+}
+
+// Everything below goes through the trait methods only, as generic code would.
+
+#[cfg(test)]
+fn digest_via_trait<D: digest::Digest>(data: &[u8]) -> Vec<u8> {
+    let mut hasher = D::new();
+    for chunk in data.chunks(41) {
+        digest::Digest::update(&mut hasher, chunk);
+    }
+    digest::Digest::finalize(hasher).to_vec()
+}
+
+#[cfg(all(test, feature = "alloc"))]
+fn xof_via_trait<H: Default + Update + ExtendableOutput>(data: &[u8], output_len: usize) -> Vec<u8> {
+    let mut hasher = H::default();
+    Update::update(&mut hasher, data);
+    let mut reader = ExtendableOutput::finalize_xof(hasher);
+    let mut output = vec![0u8; output_len];
+    // Uneven reads must stitch together like one long read
+    let (head, tail) = output.split_at_mut(output_len / 3);
+    digest::XofReader::read(&mut reader, head);
+    digest::XofReader::read(&mut reader, tail);
+    output
+}
+
+#[cfg(test)]
+fn trait_test_messages() -> Vec<Vec<u8>> {
+    [0usize, 1, 71, 72, 135, 136, 137, 300]
+        .iter()
+        .map(|&len| (0..len).map(|i| (i * 7 + 3) as u8).collect())
+        .collect()
+}
+
+#[test]
+fn test_digest_sha3_parity() {
+    for message in trait_test_messages() {
+        assert_eq!(digest_via_trait::<Sha3_224>(&message), crate::sha3_224(&message));
+        assert_eq!(digest_via_trait::<Sha3_256>(&message), crate::sha3_256(&message));
+        assert_eq!(digest_via_trait::<Sha3_384>(&message), crate::sha3_384(&message));
+        assert_eq!(digest_via_trait::<Sha3_512>(&message), crate::sha3_512(&message));
+    }
+}
+
+#[test]
+fn test_digest_keccak_parity() {
+    for message in trait_test_messages() {
+        assert_eq!(digest_via_trait::<Keccak224>(&message), crate::keccak224(&message));
+        assert_eq!(digest_via_trait::<Keccak256>(&message), crate::keccak256(&message));
+        assert_eq!(digest_via_trait::<Keccak384>(&message), crate::keccak384(&message));
+        assert_eq!(digest_via_trait::<Keccak512>(&message), crate::keccak512(&message));
+    }
+}
+
+#[test]
+fn test_digest_one_shot_and_reset() {
+    assert_eq!(<Sha3_256 as digest::Digest>::digest(b"abc").as_slice(), crate::sha3_256(b"abc"));
+
+    let mut hasher = <Sha3_512 as digest::Digest>::new();
+    digest::Digest::update(&mut hasher, b"first message");
+    assert_eq!(digest::Digest::finalize_reset(&mut hasher).as_slice(), crate::sha3_512(b"first message"));
+    digest::Digest::update(&mut hasher, b"abc");
+    assert_eq!(digest::Digest::finalize_reset(&mut hasher).as_slice(), crate::sha3_512(b"abc"));
+
+    digest::Digest::update(&mut hasher, b"discarded");
+    Reset::reset(&mut hasher);
+    assert_eq!(digest::Digest::finalize(hasher).as_slice(), crate::sha3_512(b""));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_digest_shake_parity() {
+    for message in trait_test_messages() {
+        assert_eq!(xof_via_trait::<Shake128>(&message, 500), crate::shake128(&message, 500));
+        assert_eq!(xof_via_trait::<Shake256>(&message, 500), crate::shake256(&message, 500));
+        assert_eq!(xof_via_trait::<RawShake128>(&message, 200), crate::rawshake128(&message, 200));
+        assert_eq!(xof_via_trait::<RawShake256>(&message, 200), crate::rawshake256(&message, 200));
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_digest_shake_finalize_xof_reset() {
+    let mut hasher = Shake256::default();
+    Update::update(&mut hasher, b"first message");
+    let mut output = [0u8; 64];
+    digest::XofReader::read(&mut ExtendableOutputReset::finalize_xof_reset(&mut hasher), &mut output);
+    assert_eq!(output.to_vec(), crate::shake256(b"first message", 64));
+
+    Update::update(&mut hasher, b"abc");
+    digest::XofReader::read(&mut ExtendableOutput::finalize_xof(hasher), &mut output);
+    assert_eq!(output.to_vec(), crate::shake256(b"abc", 64));
+}
+
+#[test]
+fn test_digest_hmac_sha3() {
+    use hmac::{Mac, SimpleHmac};
+
+    // HMAC computed by hand from the one-shot function, with the key padded to the rate
+    fn reference_hmac_sha3_256(key: &[u8], message: &[u8]) -> [u8; 32] {
+        let mut block_key = [0u8; 136];
+        if key.len() > 136 {
+            block_key[..32].copy_from_slice(&crate::sha3_256(key));
+        } else {
+            block_key[..key.len()].copy_from_slice(key);
+        }
+        let mut inner: Vec<u8> = block_key.iter().map(|b| b ^ 0x36).collect();
+        inner.extend_from_slice(message);
+        let mut outer: Vec<u8> = block_key.iter().map(|b| b ^ 0x5c).collect();
+        outer.extend_from_slice(&crate::sha3_256(&inner));
+        crate::sha3_256(&outer)
+    }
+
+    for key in [&[0x0b; 20][..], b"Jefe", &[0xaa; 200]] {
+        let mut mac = <SimpleHmac<Sha3_256> as Mac>::new_from_slice(key).unwrap();
+        Mac::update(&mut mac, b"what do ya want for nothing?");
+        assert_eq!(
+            mac.finalize().into_bytes().as_slice(),
+            reference_hmac_sha3_256(key, b"what do ya want for nothing?")
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use crate::ct::zeroize;
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use crate::keccak_f::KeccakF;
+use crate::round_constants::RoundConstantMode;
+
+/// The duplex construction (Bertoni, Daemen, Peeters, Van Assche, SAC 2011).
+///
+/// Every [`duplexing`](Self::duplexing) call pads one input block with pad10*1,
+/// XORs it into the outer r bits of the state, applies the permutation and
+/// returns up to r bits of the new state. Inputs are limited to r - 1 bytes so
+/// the padding always fits in the same block.
+///
+/// Security: by the duplexing-sponge lemma, every output of the duplex object
+/// is the output of the sponge with the same permutation, rate r and capacity
+/// c = b - r on a message built from the previous inputs. Any attack on the
+/// duplex object therefore gives an attack on that sponge, and the generic
+/// bound is the sponge's: success probability about N^2 / 2^(c+1) after N
+/// permutation calls, i.e. c/2 bits of security. The rate only sets the
+/// throughput; pick c as twice the wanted security level.
+///
+/// The [overwrite](Self::duplexing_overwrite) and
+/// [full-state](Self::duplexing_full_state) variants trade this generic
+/// argument for throughput and are only meant for keyed use; see their docs.
+#[derive(Clone)]
+pub struct Duplex {
+    permutation: KeccakF,
+    rounds: usize,
+    state: [u64; 25],
+    rate: usize,   // in bytes
+}
+
+impl Duplex {
+    /// Duplex object on Keccak-f[`width`] with a rate of `rate` bytes
+    pub fn new(width: usize, rate: usize) -> Self {
+        let permutation = KeccakF::new(width, RoundConstantMode::Table);
+        Self::with_rounds(width, rate, permutation.rounds())
+    }
+
+    /// Duplex object on the reduced-round Keccak-p[`width`, `rounds`]
+    pub fn with_rounds(width: usize, rate: usize, rounds: usize) -> Self {
+        let permutation = KeccakF::new(width, RoundConstantMode::Table);
+        assert!(width >= 200, "Byte-oriented duplex objects need lanes of at least 8 bits (b >= 200)");
+        assert!(rate >= 2 && rate < width / 8, "Rate must leave room for padding and a non-empty capacity");
+        Duplex {
+            permutation,
+            rounds,
+            state: [0; 25],
+            rate,
+        }
+    }
+
+    /// Rate r in bytes; inputs may be at most `rate() - 1` bytes, outputs at most `rate()`
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    /// Absorbs `input` with pad10*1, permutes and fills `output` from the outer part of the state
+    pub fn duplexing_into(&mut self, input: &[u8], output: &mut [u8]) {
+        self.duplexing_delimited(input, 0x01, output);
+    }
+
+    /// Like [`duplexing_into`](Self::duplexing_into), with up to 7 extra input bits
+    /// after the bytes of `input`.
+    ///
+    /// `delimiter` holds those trailing bits followed by the first padding bit,
+    /// the same convention as the suffix of [`crate::Keccak`]: 0x01 is no extra
+    /// bits, 0x02 a single 0 bit, 0x03 a single 1 bit.
+    pub fn duplexing_delimited(&mut self, input: &[u8], delimiter: u8, output: &mut [u8]) {
+        assert!(input.len() < self.rate, "Duplex input must be shorter than the rate");
+        assert!(output.len() <= self.rate, "Duplex output cannot exceed the rate");
+        assert!(delimiter != 0, "Delimiter must contain the first padding bit");
+
+        for (i, &byte) in input.iter().enumerate() {
+            self.permutation.xor_byte(&mut self.state, i, byte);
+        }
+        self.pad_and_permute(input.len(), delimiter, self.rate);
+        self.squeeze(output);
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn duplexing(&mut self, input: &[u8], output_len: usize) -> Vec<u8> {
+        let mut output = vec![0u8; output_len];
+        self.duplexing_into(input, &mut output);
+        output
+    }
+
+    /// Overwrite variant: the padded input replaces the outer r bits instead of
+    /// being XORed into them.
+    ///
+    /// Once the object is keyed, overwriting is as secure as XORing, and it lets
+    /// decryption feed the ciphertext back in without recomputing the keystream.
+    #[cfg(feature = "alloc")]
+    pub fn duplexing_overwrite(&mut self, input: &[u8], output_len: usize) -> Vec<u8> {
+        assert!(input.len() < self.rate, "Duplex input must be shorter than the rate");
+        assert!(output_len <= self.rate, "Duplex output cannot exceed the rate");
+
+        for i in 0..self.rate {
+            let current = self.permutation.get_byte(&self.state, i);
+            let wanted = input.get(i).copied().unwrap_or(0);
+            self.permutation.xor_byte(&mut self.state, i, current ^ wanted);
+        }
+        self.pad_and_permute(input.len(), 0x01, self.rate);
+
+        let mut output = vec![0u8; output_len];
+        self.squeeze(&mut output);
+        output
+    }
+
+    /// Full-state variant: the input is absorbed over the whole b-bit state
+    /// (up to b/8 - 1 bytes, padded against the last byte of the state), while
+    /// output is still limited to r bits.
+    ///
+    /// Only use this once the state holds a secret key. Absorbing into the
+    /// capacity voids the sponge bound above; the full-state keyed duplex of
+    /// Daemen, Mennink and Van Assche (ASIACRYPT 2017) is instead bounded by the
+    /// key length and the capacity (roughly min(k, c - log2 M) bits for M
+    /// duplexing calls), so the security no longer comes from c/2 alone.
+    #[cfg(feature = "alloc")]
+    pub fn duplexing_full_state(&mut self, input: &[u8], output_len: usize) -> Vec<u8> {
+        let state_bytes = self.permutation.width() / 8;
+        assert!(input.len() < state_bytes, "Full-state input must be shorter than the state");
+        assert!(output_len <= self.rate, "Duplex output cannot exceed the rate");
+
+        for (i, &byte) in input.iter().enumerate() {
+            self.permutation.xor_byte(&mut self.state, i, byte);
+        }
+        self.pad_and_permute(input.len(), 0x01, state_bytes);
+
+        let mut output = vec![0u8; output_len];
+        self.squeeze(&mut output);
+        output
+    }
+
+    /// pad10*1 over a block of `block_len` bytes whose first `input_len` bytes are data
+    fn pad_and_permute(&mut self, input_len: usize, delimiter: u8, block_len: usize) {
+        self.permutation.xor_byte(&mut self.state, input_len, delimiter);
+        self.permutation.xor_byte(&mut self.state, block_len - 1, 0x80);
+        self.permutation.permute_rounds(&mut self.state, self.rounds);
+    }
+
+    fn squeeze(&self, output: &mut [u8]) {
+        for (i, out) in output.iter_mut().enumerate() {
+            *out = self.permutation.get_byte(&self.state, i);
+        }
+    }
+}
+
+impl Drop for Duplex {
+    fn drop(&mut self) {
+        zeroize(&mut self.state);
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+fn padded_block(input: &[u8], rate: usize) -> Vec<u8> {
+    let mut block = input.to_vec();
+    block.resize(rate, 0);
+    block[input.len()] ^= 0x01;
+    block[rate - 1] ^= 0x80;
+    block
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_duplex_matches_sponge() {
+    // Duplexing-sponge lemma: Z_i = sponge(pad(s_0) || .. || pad(s_{i-1}) || s_i)
+    // for the sponge with plain pad10*1 (delimiter 0x01)
+    use crate::Keccak;
+
+    let rate = 136;
+    let inputs: [&[u8]; 5] = [b"", b"abc", &[0x5A; 135], &[0x00; 1], &[0xFF; 64]];
+    let mut duplex = Duplex::new(1600, rate);
+    let mut prefix = Vec::new();
+
+    for input in inputs {
+        let output = duplex.duplexing(input, rate);
+
+        let mut sponge = Keccak::new(rate, 0x01);
+        sponge.update(&prefix);
+        sponge.update(input);
+        assert_eq!(output, sponge.finalize(rate));
+
+        prefix.extend_from_slice(&padded_block(input, rate));
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_duplex_small_width_matches_sponge() {
+    use crate::KeccakSponge;
+
+    let rate = 18;
+    let mut duplex = Duplex::new(400, rate);
+    let first = duplex.duplexing(b"key material", 16);
+    let second = duplex.duplexing(b"nonce", 18);
+
+    let mut sponge = KeccakSponge::new(400, rate, 0x01);
+    sponge.update(b"key material");
+    assert_eq!(first, sponge.finalize(16));
+
+    let mut sponge = KeccakSponge::new(400, rate, 0x01);
+    sponge.update(&padded_block(b"key material", rate));
+    sponge.update(b"nonce");
+    assert_eq!(second, sponge.finalize(18));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_duplex_overwrite() {
+    let rate = 20;
+    let perm = KeccakF::new(800, RoundConstantMode::Table);
+    let mut duplex = Duplex::new(800, rate);
+    duplex.duplexing(b"key", 0);
+    let output = duplex.duplexing_overwrite(b"overwritten", rate);
+
+    // Reference: the same steps done by hand on the permutation
+    let mut state = [0u64; 25];
+    for (i, &byte) in padded_block(b"key", rate).iter().enumerate() {
+        perm.xor_byte(&mut state, i, byte);
+    }
+    perm.permute(&mut state);
+    for (i, &byte) in padded_block(b"overwritten", rate).iter().enumerate() {
+        let current = perm.get_byte(&state, i);
+        perm.xor_byte(&mut state, i, current ^ byte);
+    }
+    perm.permute(&mut state);
+    let expected: Vec<u8> = (0..rate).map(|i| perm.get_byte(&state, i)).collect();
+    assert_eq!(output, expected);
+
+    // ...which is a different state update than XORing the same input
+    let mut other = Duplex::new(800, rate);
+    other.duplexing(b"key", 0);
+    assert_ne!(other.duplexing(b"overwritten", rate), output);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_duplex_full_state() {
+    let rate = 68;
+    let perm = KeccakF::new(1600, RoundConstantMode::Table);
+    let mut duplex = Duplex::new(1600, rate);
+    let input = [0x3C; 150];
+    let output = duplex.duplexing_full_state(&input, rate);
+
+    let mut state = [0u64; 25];
+    for (i, &byte) in padded_block(&input, 200).iter().enumerate() {
+        perm.xor_byte(&mut state, i, byte);
+    }
+    perm.permute(&mut state);
+    let expected: Vec<u8> = (0..rate).map(|i| perm.get_byte(&state, i)).collect();
+    assert_eq!(output, expected);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_duplex_reduced_rounds() {
+    let mut full = Duplex::new(1600, 168);
+    let mut reduced = Duplex::with_rounds(1600, 168, 12);
+    assert_ne!(full.duplexing(b"abc", 32), reduced.duplexing(b"abc", 32));
+
+    // Keccak-p[1600, 12] with pad10*1 is TurboSHAKE with D = 0x01
+    let mut reduced = Duplex::with_rounds(1600, 168, 12);
+    assert_eq!(reduced.duplexing(b"abc", 32), crate::turboshake128(b"abc", 0x01, 32));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_duplex_delimited_trailing_bits() {
+    // One trailing 1 bit after the bytes of "abc" is the same as the sponge with suffix 0x03
+    use crate::Keccak;
+
+    let mut duplex = Duplex::new(1600, 136);
+    let mut output = [0u8; 64];
+    duplex.duplexing_delimited(b"abc", 0x03, &mut output);
+
+    let mut sponge = Keccak::new(136, 0x03);
+    sponge.update(b"abc");
+    assert_eq!(output.to_vec(), sponge.finalize(64));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+#[should_panic]
+fn test_duplex_rejects_full_rate_input() {
+    let mut duplex = Duplex::new(1600, 136);
+    duplex.duplexing(&[0u8; 136], 0);
+}
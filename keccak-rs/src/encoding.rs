@@ -0,0 +1,102 @@
+//! Encoding primitives from NIST SP 800-185, section 2.3, plus the
+//! `length_encode` used by KangarooTwelve
+//!
+//! Lengths are passed in bits, as in the specification. They are limited to
+//! `u64`, which is far below the 2^2040 bound of the standard.
+
+use alloc::vec::Vec;
+
+/// Number of bytes needed to represent `x` (at least one, so that 0 encodes as 0x00)
+fn encoded_byte_len(x: u64) -> usize {
+    let significant_bits = 64 - x.leading_zeros() as usize;
+    core::cmp::max(1, significant_bits.div_ceil(8))
+}
+
+/// left_encode(x): the byte length n of x followed by x as n big-endian bytes
+pub fn left_encode(x: u64) -> Vec<u8> {
+    let n = encoded_byte_len(x);
+    let mut out = Vec::with_capacity(n + 1);
+    out.push(n as u8);
+    out.extend_from_slice(&x.to_be_bytes()[8 - n..]);
+    out
+}
+
+/// right_encode(x): x as n big-endian bytes followed by the byte length n
+pub fn right_encode(x: u64) -> Vec<u8> {
+    let n = encoded_byte_len(x);
+    let mut out = Vec::with_capacity(n + 1);
+    out.extend_from_slice(&x.to_be_bytes()[8 - n..]);
+    out.push(n as u8);
+    out
+}
+
+/// encode_string(S): left_encode of the bit length of S, followed by S itself
+pub fn encode_string(s: &[u8]) -> Vec<u8> {
+    let mut out = left_encode(s.len() as u64 * 8);
+    out.extend_from_slice(s);
+    out
+}
+
+/// bytepad(X, w): left_encode(w) || X, zero-padded to a multiple of w bytes
+pub fn bytepad(x: &[u8], w: usize) -> Vec<u8> {
+    assert!(w > 0, "bytepad width must be positive");
+    let mut out = left_encode(w as u64);
+    out.extend_from_slice(x);
+    let padded_len = out.len().div_ceil(w) * w;
+    out.resize(padded_len, 0);
+    out
+}
+
+/// length_encode(x) from RFC 9861 (KangarooTwelve): x as the fewest big-endian
+/// bytes possible (none for 0) followed by that byte count
+pub fn length_encode(x: u64) -> Vec<u8> {
+    let n = (64 - x.leading_zeros() as usize).div_ceil(8);
+    let mut out = Vec::with_capacity(n + 1);
+    out.extend_from_slice(&x.to_be_bytes()[8 - n..]);
+    out.push(n as u8);
+    out
+}
+
+#[test]
+fn test_left_encode() {
+    assert_eq!(left_encode(0), [0x01, 0x00]);
+    assert_eq!(left_encode(168), [0x01, 0xa8]);
+    assert_eq!(left_encode(256), [0x02, 0x01, 0x00]);
+    assert_eq!(left_encode(u64::MAX), [0x08, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+}
+
+#[test]
+fn test_right_encode() {
+    assert_eq!(right_encode(0), [0x00, 0x01]);
+    assert_eq!(right_encode(256), [0x01, 0x00, 0x02]);
+    assert_eq!(right_encode(0x0102_0304), [0x01, 0x02, 0x03, 0x04, 0x04]);
+}
+
+#[test]
+fn test_length_encode() {
+    assert_eq!(length_encode(0), [0x00]);
+    assert_eq!(length_encode(12), [0x0C, 0x01]);
+    assert_eq!(length_encode(65538), [0x01, 0x00, 0x02, 0x03]);
+}
+
+#[test]
+fn test_encode_string() {
+    assert_eq!(encode_string(b""), [0x01, 0x00]);
+    // "Email Signature" is 15 bytes = 120 bits
+    let encoded = encode_string(b"Email Signature");
+    assert_eq!(&encoded[..2], &[0x01, 0x78]);
+    assert_eq!(&encoded[2..], b"Email Signature");
+}
+
+#[test]
+fn test_bytepad() {
+    let padded = bytepad(&[0xAA, 0xBB], 8);
+    assert_eq!(padded, [0x01, 0x08, 0xAA, 0xBB, 0x00, 0x00, 0x00, 0x00]);
+
+    // Already a multiple of w: no extra zero block is added
+    let padded = bytepad(&[0xAA; 6], 8);
+    assert_eq!(padded.len(), 8);
+
+    let padded = bytepad(&encode_string(b""), 168);
+    assert_eq!(padded.len(), 168);
+}
@@ -0,0 +1,84 @@
+use core::fmt;
+
+/// Returned by the authenticated encryption modes when a tag does not verify.
+///
+/// Deliberately carries no detail: callers must not learn anything about the
+/// rejected ciphertext beyond the fact that it was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthenticationError;
+
+impl fmt::Display for AuthenticationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("authentication failed")
+    }
+}
+
+impl core::error::Error for AuthenticationError {}
+
+/// Why a [`crate::SpongeParams`] or [`crate::DomainSuffix`] was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpongeParamsError {
+    /// Neither a rate, a capacity nor a security level was given
+    MissingCapacity,
+    /// The capacity in bits is not a whole number of bytes
+    CapacityNotByteAligned(usize),
+    /// The rate in bytes is 0 or leaves no capacity in the 200-byte state
+    InvalidRate(usize),
+    /// The suffix byte has no padding bit (0x00)
+    InvalidSuffix(u8),
+    /// Zero rounds of Keccak-p[1600] would make the sponge linear; more than
+    /// 255 only repeat round constants
+    InvalidRounds(usize),
+}
+
+impl fmt::Display for SpongeParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpongeParamsError::MissingCapacity => f.write_str("no rate, capacity or security level given"),
+            SpongeParamsError::CapacityNotByteAligned(bits) => {
+                write!(f, "capacity of {} bits is not a multiple of 8", bits)
+            }
+            SpongeParamsError::InvalidRate(rate) => {
+                write!(f, "rate of {} bytes is outside 1..=199", rate)
+            }
+            SpongeParamsError::InvalidSuffix(suffix) => {
+                write!(f, "domain suffix {:#04x} has no padding bit", suffix)
+            }
+            SpongeParamsError::InvalidRounds(rounds) => write!(f, "{} rounds is not a valid round count", rounds),
+        }
+    }
+}
+
+impl core::error::Error for SpongeParamsError {}
+
+/// Why [`crate::Keccak::import_state`] rejected a serialized midstate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateImportError {
+    /// The input is not exactly [`crate::KECCAK_STATE_LEN`] bytes long
+    InvalidLength(usize),
+    /// The input does not start with the midstate magic bytes
+    BadMagic,
+    /// The format version is not one this crate can read
+    UnsupportedVersion(u8),
+    /// The checksum does not match: the data was truncated or corrupted
+    ChecksumMismatch,
+    /// The fields are internally inconsistent (e.g. an offset beyond the rate)
+    InvalidParameters,
+    /// The midstate belongs to a different algorithm than the one importing it
+    AlgorithmMismatch,
+}
+
+impl fmt::Display for StateImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateImportError::InvalidLength(len) => write!(f, "midstate of {} bytes has the wrong length", len),
+            StateImportError::BadMagic => f.write_str("not a serialized Keccak midstate"),
+            StateImportError::UnsupportedVersion(version) => write!(f, "unsupported midstate version {}", version),
+            StateImportError::ChecksumMismatch => f.write_str("midstate checksum mismatch"),
+            StateImportError::InvalidParameters => f.write_str("midstate has inconsistent parameters"),
+            StateImportError::AlgorithmMismatch => f.write_str("midstate belongs to a different algorithm"),
+        }
+    }
+}
+
+impl core::error::Error for StateImportError {}
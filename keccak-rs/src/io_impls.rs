@@ -0,0 +1,275 @@
+//! `std::io` adapters: the incremental hashers are `Write` sinks and the XOF
+//! reader is an endless `Read` source, so `io::copy` can stream into and out of
+//! them without loading whole inputs into memory.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use crate::{
+    CShake128, CShake256, Keccak, Keccak224, Keccak256, Keccak384, Keccak512, Kmac128, Kmac256, Kt128, Kt256,
+    ParallelHash128, ParallelHash256, RawShake128, RawShake256, Sha3_224, Sha3_256, Sha3_384, Sha3_512, Shake128,
+    Shake256, TurboShake128, TurboShake256, XofReader,
+};
+
+/// Read size used by [`hash_reader`] and [`hash_file`]. `io::copy` reads 8 KiB
+/// at a time; 64 KiB needs an eighth of the read calls on files and pipes.
+const READ_BUFFER_LEN: usize = 64 * 1024;
+
+/// A message ended by a partial byte (see [`Keccak::update_bits`]) takes no
+/// more input; `update` would panic, `write` reports it instead.
+fn check_whole_bytes(sponge: &Keccak) -> io::Result<()> {
+    if sponge.bits != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no input can follow a message ending in a partial byte",
+        ));
+    }
+    Ok(())
+}
+
+macro_rules! impl_write {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl Write for $name {
+                fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                    $name::update(self, buf);
+                    Ok(buf.len())
+                }
+
+                fn flush(&mut self) -> io::Result<()> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+    // Hashers with `update_bits`, which can end the message in a partial byte
+    (bits: $($name:ident),* $(,)?) => {
+        $(
+            impl Write for $name {
+                fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                    check_whole_bytes(&self.sponge)?;
+                    $name::update(self, buf);
+                    Ok(buf.len())
+                }
+
+                fn flush(&mut self) -> io::Result<()> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl Write for Keccak {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        check_whole_bytes(self)?;
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl_write!(
+    bits:
+    Sha3_224, Sha3_256, Sha3_384, Sha3_512,
+    Keccak224, Keccak256, Keccak384, Keccak512,
+    Shake128, Shake256, RawShake128, RawShake256,
+);
+impl_write!(
+    CShake128, CShake256, Kmac128, Kmac256,
+    ParallelHash128, ParallelHash256,
+    TurboShake128, TurboShake256, Kt128, Kt256,
+);
+
+/// Never reaches end of file: bound it with [`Read::take`] before `io::copy`.
+impl Read for XofReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        XofReader::read(self, buf);
+        Ok(buf.len())
+    }
+}
+
+/// Hash functions available to [`hash_reader`] and [`hash_file`]; the SHAKE
+/// variants carry their output length in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    Sha3_224,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
+    Shake128(usize),
+    Shake256(usize),
+    Keccak224,
+    Keccak256,
+    Keccak384,
+    Keccak512,
+}
+
+impl HashAlgorithm {
+    /// Digest length in bytes
+    pub fn output_len(&self) -> usize {
+        match *self {
+            HashAlgorithm::Sha3_224 | HashAlgorithm::Keccak224 => 28,
+            HashAlgorithm::Sha3_256 | HashAlgorithm::Keccak256 => 32,
+            HashAlgorithm::Sha3_384 | HashAlgorithm::Keccak384 => 48,
+            HashAlgorithm::Sha3_512 | HashAlgorithm::Keccak512 => 64,
+            HashAlgorithm::Shake128(output_len) | HashAlgorithm::Shake256(output_len) => output_len,
+        }
+    }
+
+    fn sponge(&self) -> Keccak {
+        match *self {
+            HashAlgorithm::Sha3_224 => Keccak::new(144, 0x06),
+            HashAlgorithm::Sha3_256 => Keccak::new(136, 0x06),
+            HashAlgorithm::Sha3_384 => Keccak::new(104, 0x06),
+            HashAlgorithm::Sha3_512 => Keccak::new(72, 0x06),
+            HashAlgorithm::Shake128(_) => Keccak::new(168, 0x1F),
+            HashAlgorithm::Shake256(_) => Keccak::new(136, 0x1F),
+            HashAlgorithm::Keccak224 => Keccak::new(144, 0x01),
+            HashAlgorithm::Keccak256 => Keccak::new(136, 0x01),
+            HashAlgorithm::Keccak384 => Keccak::new(104, 0x01),
+            HashAlgorithm::Keccak512 => Keccak::new(72, 0x01),
+        }
+    }
+}
+
+/// Hashes everything `reader` yields until end of file, retrying interrupted reads
+pub fn hash_reader<R: Read>(mut reader: R, algorithm: HashAlgorithm) -> io::Result<Vec<u8>> {
+    let mut sponge = algorithm.sponge();
+    let mut buffer = vec![0u8; READ_BUFFER_LEN];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => sponge.update(&buffer[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(sponge.finalize(algorithm.output_len()))
+}
+
+/// Hashes the contents of the file at `path` without reading it into memory
+pub fn hash_file<P: AsRef<Path>>(path: P, algorithm: HashAlgorithm) -> io::Result<Vec<u8>> {
+    hash_reader(File::open(path)?, algorithm)
+}
+
+#[test]
+fn test_io_copy_into_hasher() {
+    let data: Vec<u8> = (0..1000).map(|i| (i * 13) as u8).collect();
+    let mut hasher = Sha3_256::new();
+    io::copy(&mut &data[..], &mut hasher).unwrap();
+    assert_eq!(hasher.finalize(), crate::sha3_256(&data));
+
+    let mut hasher = Keccak256::new();
+    for chunk in data.chunks(77) {
+        hasher.write_all(chunk).unwrap();
+    }
+    hasher.flush().unwrap();
+    assert_eq!(hasher.finalize(), crate::keccak256(&data));
+
+    let mut hasher = Shake128::new();
+    let counter = 1234;
+    write!(hasher, "counter={counter}").unwrap();
+    assert_eq!(hasher.finalize(64), crate::shake128(b"counter=1234", 64));
+}
+
+#[test]
+fn test_write_after_partial_byte_is_an_error() {
+    let mut sponge = Keccak::new(136, 0x06);
+    sponge.update_bits(&[0x13], 5);
+    assert_eq!(sponge.write(b"more").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+    let mut hasher = Sha3_256::new();
+    hasher.write_all(b"abc").unwrap();
+    hasher.update_bits(&[0x01], 3);
+    assert_eq!(hasher.write_all(b"more").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+    let mut hasher = Shake128::new();
+    hasher.update_bits(&[0x01], 1);
+    assert_eq!(io::copy(&mut &b"more"[..], &mut hasher).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_xof_reader_read() {
+    let mut hasher = Shake256::new();
+    hasher.update(b"abc");
+    let mut reader = hasher.finalize_xof();
+
+    let mut output = [0u8; 300];
+    reader.read_exact(&mut output[..7]).unwrap();
+    reader.read_exact(&mut output[7..]).unwrap();
+    assert_eq!(output.to_vec(), crate::shake256(b"abc", 300));
+
+    let mut hasher = Shake128::new();
+    hasher.update(b"abc");
+    let mut copied = Vec::new();
+    io::copy(&mut hasher.finalize_xof().take(500), &mut copied).unwrap();
+    assert_eq!(copied, crate::shake128(b"abc", 500));
+}
+
+#[test]
+fn test_hash_reader_million_a() {
+    // Same NIST vectors as the in-memory million_a tests, streamed instead
+    let million_a = || io::repeat(b'a').take(1_000_000);
+    assert_eq!(
+        hex::encode(hash_reader(million_a(), HashAlgorithm::Sha3_256).unwrap()),
+        "5c8875ae474a3634ba4fd55ec85bffd661f32aca75c6d699d0cdcb6c115891c1"
+    );
+    assert_eq!(
+        hex::encode(hash_reader(million_a(), HashAlgorithm::Sha3_512).unwrap()),
+        "3c3a876da14034ab60627c077bb98f7e120a2a5370212dffb3385a18d4f38859ed311d0a9d5141ce9cc5c66ee689b266a8aa18ace8282a0e0db596c90b0a7b87"
+    );
+}
+
+#[test]
+fn test_hash_reader_matches_one_shots() {
+    let data: Vec<u8> = (0..200_000).map(|i| (i % 251) as u8).collect();
+    let hash = |algorithm| hash_reader(&data[..], algorithm).unwrap();
+    assert_eq!(hash(HashAlgorithm::Sha3_224), crate::sha3_224(&data));
+    assert_eq!(hash(HashAlgorithm::Sha3_384), crate::sha3_384(&data));
+    assert_eq!(hash(HashAlgorithm::Shake128(100)), crate::shake128(&data, 100));
+    assert_eq!(hash(HashAlgorithm::Shake256(100)), crate::shake256(&data, 100));
+    assert_eq!(hash(HashAlgorithm::Keccak256), crate::keccak256(&data));
+    assert_eq!(hash(HashAlgorithm::Keccak512), crate::keccak512(&data));
+}
+
+#[test]
+fn test_hash_reader_errors() {
+    // Fails with `error` on the first read, then reads from `data`
+    struct FailOnce {
+        error: Option<io::ErrorKind>,
+        data: &'static [u8],
+    }
+
+    impl Read for FailOnce {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.error.take() {
+                Some(kind) => Err(kind.into()),
+                None => self.data.read(buf),
+            }
+        }
+    }
+
+    let interrupted = FailOnce { error: Some(io::ErrorKind::Interrupted), data: b"abc" };
+    assert_eq!(hash_reader(interrupted, HashAlgorithm::Sha3_256).unwrap(), crate::sha3_256(b"abc"));
+
+    let broken = FailOnce { error: Some(io::ErrorKind::BrokenPipe), data: b"abc" };
+    assert_eq!(hash_reader(broken, HashAlgorithm::Sha3_256).unwrap_err().kind(), io::ErrorKind::BrokenPipe);
+
+    let missing = std::env::temp_dir().join("keccak-rs-no-such-file");
+    assert_eq!(hash_file(missing, HashAlgorithm::Sha3_256).unwrap_err().kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_hash_file() {
+    let path = std::env::temp_dir().join(format!("keccak-rs-hash-file-{}", std::process::id()));
+    let data: Vec<u8> = (0..150_000).map(|i| (i * 31) as u8).collect();
+    std::fs::write(&path, &data).unwrap();
+    let hash = hash_file(&path, HashAlgorithm::Sha3_512);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(hash.unwrap(), crate::sha3_512(&data));
+}
@@ -0,0 +1,297 @@
+use crate::encoding::length_encode;
+use crate::parallel::{default_threads, LeafStream};
+use crate::{Keccak, XofReader};
+
+/// KangarooTwelve chunk size B: S_0 goes into the final node, S_1.. become leaves
+const CHUNK_SIZE: usize = 8192;
+
+/// Rounds of the underlying TurboSHAKE, Keccak-p[1600, 12]
+const ROUNDS: usize = 12;
+
+/// Marker appended to S_0 in the final node once there is more than one chunk (110^62)
+const FINAL_NODE_MARKER: [u8; 8] = [0x03, 0, 0, 0, 0, 0, 0, 0];
+
+macro_rules! kangaroo_twelve {
+    ($(#[$doc:meta])* $name:ident, $rate:expr, $cv_len:expr) => {
+        $(#[$doc])*
+        ///
+        /// Input is streamed through `update`: the first 8 KiB chunk is absorbed
+        /// straight into the final node, later chunks are hashed as leaves on
+        /// worker threads, in place when `update` gets a large slice. The output does not depend on the
+        /// number of threads or on how the input was split between `update` calls.
+        #[derive(Clone)]
+        pub struct $name {
+            final_node: Keccak,
+            customization: Vec<u8>,
+            first_chunk_len: usize,  // bytes of S_0 absorbed into the final node
+            stream: LeafStream,      // bytes past S_0 not yet hashed into leaves
+            leaves: u64,             // chaining values absorbed into the final node
+        }
+
+        impl $name {
+            pub fn new(customization: &[u8]) -> Self {
+                $name {
+                    // Single-node delimiter; switched to 0x06 in finish() if leaves show up
+                    final_node: Keccak::with_rounds($rate, 0x07, ROUNDS),
+                    customization: customization.to_vec(),
+                    first_chunk_len: 0,
+                    stream: LeafStream::new(CHUNK_SIZE, default_threads()),
+                    leaves: 0,
+                }
+            }
+
+            /// Sets the number of worker threads; 0 and 1 both hash on the calling thread
+            pub fn with_threads(mut self, threads: usize) -> Self {
+                self.stream.set_threads(threads);
+                self
+            }
+
+            pub fn update(&mut self, mut input: &[u8]) {
+                if self.first_chunk_len < CHUNK_SIZE {
+                    let take = std::cmp::min(CHUNK_SIZE - self.first_chunk_len, input.len());
+                    self.final_node.update(&input[..take]);
+                    self.first_chunk_len += take;
+                    input = &input[take..];
+                }
+
+                let $name { final_node, stream, leaves, .. } = self;
+                stream.update(input, &Self::leaf, |cvs| Self::absorb_cvs(final_node, leaves, cvs));
+            }
+
+            fn leaf(chunk: &[u8], out: &mut Vec<u8>) {
+                let mut leaf = Keccak::with_rounds($rate, 0x0B, ROUNDS);
+                leaf.update(chunk);
+                let mut cv = [0u8; $cv_len];
+                leaf.finalize_xof().read(&mut cv);
+                out.extend_from_slice(&cv);
+            }
+
+            fn absorb_cvs(final_node: &mut Keccak, leaves: &mut u64, cvs: &[u8]) {
+                if *leaves == 0 {
+                    final_node.update(&FINAL_NODE_MARKER);
+                }
+                *leaves += (cvs.len() / $cv_len) as u64;
+                final_node.update(cvs);
+            }
+
+            /// Appends C || length_encode(|C|), flushes the last leaves and closes the final node
+            fn finish(mut self) -> Keccak {
+                let customization = std::mem::take(&mut self.customization);
+                self.update(&customization);
+                self.update(&length_encode(customization.len() as u64));
+
+                let $name { final_node, stream, leaves, .. } = &mut self;
+                stream.finish(&Self::leaf, |cvs| Self::absorb_cvs(final_node, leaves, cvs));
+
+                if self.leaves > 0 {
+                    self.final_node.update(&length_encode(self.leaves));
+                    self.final_node.update(&[0xFF, 0xFF]);
+                    self.final_node.delimiter = 0x06;
+                }
+                self.final_node
+            }
+
+            pub fn finalize(self, output_len: usize) -> Vec<u8> {
+                self.finish().finalize(output_len)
+            }
+
+            pub fn finalize_xof(self) -> XofReader {
+                self.finish().finalize_xof()
+            }
+        }
+    };
+}
+
+kangaroo_twelve!(
+    /// KT128 (KangarooTwelve, RFC 9861) on TurboSHAKE128, with customization string C
+    Kt128, 168, 32
+);
+kangaroo_twelve!(
+    /// KT256 (RFC 9861) on TurboSHAKE256, with customization string C
+    Kt256, 136, 64
+);
+
+pub fn kt128(data: &[u8], customization: &[u8], output_len: usize) -> Vec<u8> {
+    let mut hasher = Kt128::new(customization);
+    hasher.update(data);
+    hasher.finalize(output_len)
+}
+
+pub fn kt256(data: &[u8], customization: &[u8], output_len: usize) -> Vec<u8> {
+    let mut hasher = Kt256::new(customization);
+    hasher.update(data);
+    hasher.finalize(output_len)
+}
+
+// KangarooTwelve test vectors from RFC 9861, section 5
+
+#[cfg(test)]
+use crate::turboshake::ptn;
+
+#[test]
+fn test_kt128_empty() {
+    assert_eq!(
+        hex::encode(kt128(b"", b"", 32)),
+        "1ac2d450fc3b4205d19da7bfca1b37513c0803577ac7167f06fe2ce1f0ef39e5"
+    );
+    assert_eq!(
+        hex::encode(kt128(b"", b"", 64)),
+        "1ac2d450fc3b4205d19da7bfca1b37513c0803577ac7167f06fe2ce1f0ef39e54269c056b8c82e48276038b6d292966cc07a3d4645272e31ff38508139eb0a71"
+    );
+    let long = kt128(b"", b"", 10032);
+    assert_eq!(
+        hex::encode(&long[10000..]),
+        "e8dc563642f7228c84684c898405d3a834799158c079b12880277a1d28e2ff6d"
+    );
+}
+
+#[test]
+fn test_kt128_ptn() {
+    let expected = [
+        "2bda92450e8b147f8a7cb629e784a058efca7cf7d8218e02d345dfaa65244a1f",
+        "6bf75fa2239198db4772e36478f8e19b0f371205f6a9a93a273f51df37122888",
+        "0c315ebcdedbf61426de7dcf8fb725d1e74675d7f5327a5067f367b108ecb67c",
+        "cb552e2ec77d9910701d578b457ddf772c12e322e4ee7fe417f92c758f0d59d0",
+        "8701045e22205345ff4dda05555cbb5c3af1a771c2b89baef37db43d9998b9fe",
+        "844d610933b1b9963cbdeb5ae3b6b05cc7cbd67ceedf883eb678a0a8e0371682",
+        "3c390782a8a4e89fa6367f72feaaf13255c8d95878481d3cd8ce85f58e880af8",
+    ];
+    for (i, expected) in expected.iter().enumerate() {
+        let data = ptn(17usize.pow(i as u32));
+        assert_eq!(hex::encode(kt128(&data, b"", 32)), *expected, "ptn(17^{})", i);
+    }
+}
+
+#[test]
+fn test_kt128_customization() {
+    let messages: [&[u8]; 4] = [b"", &[0xFF], &[0xFF; 3], &[0xFF; 7]];
+    let expected = [
+        "fab658db63e94a246188bf7af69a133045f46ee984c56e3c3328caaf1aa1a583",
+        "d848c5068ced736f4462159b9867fd4c20b808acc3d5bc48e0b06ba0a3762ec4",
+        "c389e5009ae57120854c2e8c64670ac01358cf4c1baf89447a724234dc7ced74",
+        "75d2f86a2e644566726b4fbcfc5657b9dbcf070c7b0dca06450ab291d7443bcf",
+    ];
+    for (i, (message, expected)) in messages.iter().zip(expected.iter()).enumerate() {
+        let customization = ptn(41usize.pow(i as u32));
+        assert_eq!(hex::encode(kt128(message, &customization, 32)), *expected, "C = ptn(41^{})", i);
+    }
+}
+
+#[test]
+fn test_kt128_chunk_boundaries() {
+    assert_eq!(
+        hex::encode(kt128(&ptn(8191), b"", 32)),
+        "1b577636f723643e990cc7d6a659837436fd6a103626600eb8301cd1dbe553d6"
+    );
+    assert_eq!(
+        hex::encode(kt128(&ptn(8192), b"", 32)),
+        "48f256f6772f9edfb6a8b661ec92dc93b95ebd05a08a17b39ae3490870c926c3"
+    );
+    assert_eq!(
+        hex::encode(kt128(&ptn(8192), &ptn(8189), 32)),
+        "3ed12f70fb05ddb58689510ab3e4d23c6c6033849aa01e1d8c220a297fedcd0b"
+    );
+    assert_eq!(
+        hex::encode(kt128(&ptn(8192), &ptn(8190), 32)),
+        "6a7c1b6a5cd0d8c9ca943a4a216cc64604559a2ea45f78570a15253d67ba00ae"
+    );
+}
+
+#[test]
+fn test_kt256_empty() {
+    assert_eq!(
+        hex::encode(kt256(b"", b"", 64)),
+        "b23d2e9cea9f4904e02bec06817fc10ce38ce8e93ef4c89e6537076af8646404e3e8b68107b8833a5d30490aa33482353fd4adc7148ecb782855003aaebde4a9"
+    );
+    let long = kt256(b"", b"", 10032);
+    assert_eq!(
+        hex::encode(&long[10000..]),
+        "ad4a1d718cf950506709a4c33396139b4449041fc79a05d68da35f1e453522e0"
+    );
+}
+
+#[test]
+fn test_kt256_ptn() {
+    let expected = [
+        "0d005a194085360217128cf17f91e1f71314efa5564539d444912e3437efa17f82db6f6ffe76e781eaa068bce01f2bbf81eacb983d7230f2fb02834a21b1ddd0",
+        "1ba3c02b1fc514474f06c8979978a9056c8483f4a1b63d0dccefe3a28a2f323e1cdcca40ebf006ac76ef0397152346837b1277d3e7faa9c9653b19075098527b",
+        "de8ccbc63e0f133ebb4416814d4c66f691bbf8b6a61ec0a7700f836b086cb029d54f12ac7159472c72db118c35b4e6aa213c6562caaa9dcc518959e69b10f3ba",
+        "647efb49fe9d717500171b41e7f11bd491544443209997ce1c2530d15eb1ffbb598935ef954528ffc152b1e4d731ee2683680674365cd191d562bae753b84aa5",
+        "b06275d284cd1cf205bcbe57dccd3ec1ff6686e3ed15776383e1f2fa3c6ac8f08bf8a162829db1a44b2a43ff83dd89c3cf1ceb61ede659766d5ccf817a62ba8d",
+        "9473831d76a4c7bf77ace45b59f1458b1673d64bcd877a7c66b2664aa6dd149e60eab71b5c2bab858c074ded81ddce2b4022b5215935c0d4d19bf511aeeb0772",
+        "0652b740d78c5e1f7c8dcc1777097382768b7ff38f9a7a20f29f413bb1b3045b31a5578f568f911e09cf44746da84224a5266e96a4a535e871324e4f9c7004da",
+    ];
+    for (i, expected) in expected.iter().enumerate() {
+        let data = ptn(17usize.pow(i as u32));
+        assert_eq!(hex::encode(kt256(&data, b"", 64)), *expected, "ptn(17^{})", i);
+    }
+}
+
+#[test]
+fn test_kt256_customization() {
+    let messages: [&[u8]; 4] = [b"", &[0xFF], &[0xFF; 3], &[0xFF; 7]];
+    let expected = [
+        "9280f5cc39b54a5a594ec63de0bb99371e4609d44bf845c2f5b8c316d72b159811f748f23e3fabbe5c3226ec96c62186df2d33e9df74c5069ceecbb4dd10eff6",
+        "47ef96dd616f200937aa7847e34ec2feae8087e3761dc0f8c1a154f51dc9ccf845d7adbce57ff64b639722c6a1672e3bf5372d87e00aff89be97240756998853",
+        "3b48667a5051c5966c53c5d42b95de451e05584e7806e2fb765eda959074172cb438a9e91dde337c98e9c41bed94c4e0aef431d0b64ef2324f7932caa6f54969",
+        "e0911cc00025e1540831e266d94add9b98712142b80d2629e643aac4efaf5a3a30a88cbf4ac2a91a2432743054fbcc9897670e86ba8cec2fc2ace9c966369724",
+    ];
+    for (i, (message, expected)) in messages.iter().zip(expected.iter()).enumerate() {
+        let customization = ptn(41usize.pow(i as u32));
+        assert_eq!(hex::encode(kt256(message, &customization, 64)), *expected, "C = ptn(41^{})", i);
+    }
+}
+
+#[test]
+fn test_kt256_chunk_boundaries() {
+    assert_eq!(
+        hex::encode(kt256(&ptn(8191), b"", 64)),
+        "3081434d93a4108d8d8a3305b89682cebedc7ca4ea8a3ce869fbb73cbe4a58eef6f24de38ffc170514c70e7ab2d01f03812616e863d769afb3753193ba045b20"
+    );
+    assert_eq!(
+        hex::encode(kt256(&ptn(8192), b"", 64)),
+        "c6ee8e2ad3200c018ac87aaa031cdac22121b412d07dc6e0dccbb53423747e9a1c18834d99df596cf0cf4b8dfafb7bf02d139d0c9035725adc1a01b7230a41fa"
+    );
+    assert_eq!(
+        hex::encode(kt256(&ptn(8192), &ptn(8189), 64)),
+        "74e47879f10a9c5d11bd2da7e194fe57e86378bf3c3f7448eff3c576a0f18c5caae0999979512090a7f348af4260d4de3c37f1ecaf8d2c2c96c1d16c64b12496"
+    );
+    assert_eq!(
+        hex::encode(kt256(&ptn(8192), &ptn(8190), 64)),
+        "f4b5908b929ffe01e0f79ec2f21243d41a396b2e7303a6af1d6399cd6c7a0a2dd7c4f607e8277f9c9b1cb4ab9ddc59d4b92d1fc7558441f1832c3279a4241b8b"
+    );
+}
+
+#[test]
+fn test_kt_streaming_and_threads() {
+    let data = ptn(300_000);
+    let expected128 = "474c9a2308cf8eed987c32edf719ff521e64ffa5a3ab1b9db5c0d9feb3acc584";
+    let expected256 = "2cd458d01dce9d24f54d4c24ab38c8a611d1b7fd0a27e0d2ddb606cd1418ff33ccd11d4451b5b7f105a8abbe8576e2a556af5cece313659ef89bb364399eda7e";
+
+    for threads in [1, 2, 5] {
+        let mut hasher = Kt128::new(b"cust").with_threads(threads);
+        for chunk in data.chunks(5000) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hex::encode(hasher.finalize(32)), expected128, "threads = {}", threads);
+
+        let mut hasher = Kt256::new(b"cust").with_threads(threads);
+        hasher.update(&data);
+        assert_eq!(hex::encode(hasher.finalize(64)), expected256, "threads = {}", threads);
+    }
+}
+
+#[test]
+fn test_kt_batched_update() {
+    // Several batches' worth of input so leaves are hashed during update()
+    let data = ptn((3 << 20) + 4321);
+    let mut single = Kt128::new(b"").with_threads(1);
+    single.update(&data);
+    let expected = single.finalize(32);
+
+    let mut hasher = Kt128::new(b"").with_threads(3);
+    for chunk in data.chunks(65_537) {
+        hasher.update(chunk);
+    }
+    assert_eq!(hasher.finalize(32), expected);
+}
@@ -0,0 +1,583 @@
+use crate::ct::zeroize;
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use crate::keccak_f1600::RHO;
+use crate::round_constants::{get_round_constant, RoundConstantMode};
+
+/// Keccak-f[b] for any of the seven widths b = 25 * w, w = 1, 2, 4, .., 64.
+///
+/// The state is kept as 25 `u64` lanes of which only the low `w` bits are used,
+/// so all widths share one representation (and `[u64; 25]` with the 1600-bit
+/// permutation). Per FIPS 202 / the Keccak reference, width b = 25 * 2^l runs
+/// 12 + 2l rounds, round constants are truncated to w bits and the rho offsets
+/// are taken mod w.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeccakF {
+    lane_bits: u32,
+    mode: RoundConstantMode,
+}
+
+impl KeccakF {
+    /// `width` is the permutation width b in bits (25, 50, 100, 200, 400, 800 or 1600)
+    pub fn new(width: usize, mode: RoundConstantMode) -> Self {
+        assert!(
+            width.is_multiple_of(25) && (width / 25).is_power_of_two() && width <= 1600,
+            "Keccak-f width must be one of 25, 50, 100, 200, 400, 800, 1600"
+        );
+        KeccakF {
+            lane_bits: (width / 25) as u32,
+            mode,
+        }
+    }
+
+    /// Permutation width b in bits
+    pub fn width(&self) -> usize {
+        25 * self.lane_bits as usize
+    }
+
+    /// Lane size w in bits
+    pub fn lane_bits(&self) -> u32 {
+        self.lane_bits
+    }
+
+    /// Number of rounds of Keccak-f[b]: 12 + 2l with w = 2^l
+    pub fn rounds(&self) -> usize {
+        12 + 2 * self.lane_bits.trailing_zeros() as usize
+    }
+
+    fn lane_mask(&self) -> u64 {
+        u64::MAX >> (64 - self.lane_bits)
+    }
+
+    fn rotate(&self, lane: u64, offset: u32) -> u64 {
+        let w = self.lane_bits;
+        let offset = offset % w;
+        if offset == 0 {
+            lane
+        } else {
+            ((lane << offset) | (lane >> (w - offset))) & self.lane_mask()
+        }
+    }
+
+    pub fn theta(&self, state: &mut [u64; 25]) {
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ self.rotate(c[(x + 1) % 5], 1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+    }
+
+    pub fn rho_pi(&self, state: &mut [u64; 25]) {
+        let mut moved = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                moved[y + 5 * ((2 * x + 3 * y) % 5)] = self.rotate(state[x + 5 * y], RHO[x][y]);
+            }
+        }
+        *state = moved;
+    }
+
+    pub fn chi(&self, state: &mut [u64; 25]) {
+        let mask = self.lane_mask();
+        for y in 0..5 {
+            let mut row = [0u64; 5];
+            row.copy_from_slice(&state[5 * y..5 * y + 5]);
+            for x in 0..5 {
+                state[x + 5 * y] = row[x] ^ ((!row[(x + 1) % 5] & mask) & row[(x + 2) % 5]);
+            }
+        }
+    }
+
+    /// `round` is the round index i_r, reduced mod 255 (see [`Self::permute_rounds`])
+    pub fn iota(&self, state: &mut [u64; 25], round: usize) {
+        state[0] ^= get_round_constant(round, self.mode) & self.lane_mask();
+    }
+
+    pub fn round(&self, state: &mut [u64; 25], round_index: usize) {
+        self.theta(state);
+        self.rho_pi(state);
+        self.chi(state);
+        self.iota(state, round_index);
+    }
+
+    /// Keccak-f[b]: all 12 + 2l rounds
+    pub fn permute(&self, state: &mut [u64; 25]) {
+        self.permute_rounds(state, self.rounds());
+    }
+
+    /// Keccak-p[b, nr]: rounds i_r = 12 + 2l - nr .. 12 + 2l - 1, i.e. the last nr
+    /// rounds of Keccak-f[b]. Negative indices (nr > 12 + 2l) are taken mod 255.
+    pub fn permute_rounds(&self, state: &mut [u64; 25], rounds: usize) {
+        let full_rounds = self.rounds() as i64;
+        for i in 0..rounds {
+            let round_index = (full_rounds - rounds as i64 + i as i64).rem_euclid(255) as usize;
+            self.round(state, round_index);
+        }
+    }
+
+    /// XORs `byte` into byte `index` of the state, lanes laid out little-endian.
+    /// Only meaningful for widths with whole-byte lanes (b >= 200).
+    pub(crate) fn xor_byte(&self, state: &mut [u64; 25], index: usize, byte: u8) {
+        let lane_bytes = self.lane_bits as usize / 8;
+        state[index / lane_bytes] ^= (byte as u64) << (8 * (index % lane_bytes));
+    }
+
+    pub(crate) fn get_byte(&self, state: &[u64; 25], index: usize) -> u8 {
+        let lane_bytes = self.lane_bits as usize / 8;
+        (state[index / lane_bytes] >> (8 * (index % lane_bytes))) as u8
+    }
+}
+
+/// Byte-oriented sponge Keccak[r, c] on Keccak-f[b] for b = 200, 400, 800 or 1600.
+///
+/// Same padding rules as [`crate::Keccak`] (delimiter carries the domain suffix
+/// and the first padding bit), but with a selectable permutation width for
+/// small-state instances. Widths below 200 have lanes smaller than a byte and
+/// are only available as raw permutations.
+#[derive(Clone)]
+pub struct KeccakSponge {
+    permutation: KeccakF,
+    state: [u64; 25],
+    rate: usize,     // in bytes
+    offset: usize,   // current byte offset in the rate part of the state
+    delimiter: u8,
+}
+
+impl KeccakSponge {
+    pub fn new(width: usize, rate: usize, delimiter: u8) -> Self {
+        let permutation = KeccakF::new(width, RoundConstantMode::Table);
+        assert!(width >= 200, "Byte-oriented sponges need lanes of at least 8 bits (b >= 200)");
+        assert!(rate > 0 && rate < width / 8, "Rate must be less than the state size (b / 8 bytes)");
+        assert!(delimiter != 0, "Delimiter must contain the first padding bit");
+        KeccakSponge {
+            permutation,
+            state: [0; 25],
+            rate,
+            offset: 0,
+            delimiter,
+        }
+    }
+
+    pub fn update(&mut self, input: &[u8]) {
+        for &byte in input {
+            self.permutation.xor_byte(&mut self.state, self.offset, byte);
+            self.offset += 1;
+
+            if self.offset == self.rate {
+                self.permutation.permute(&mut self.state);
+                self.offset = 0;
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn finalize(self, output_len: usize) -> Vec<u8> {
+        let mut output = vec![0u8; output_len];
+        self.finalize_into(&mut output);
+        output
+    }
+
+    /// Pad and squeeze `output.len()` bytes into `output` without allocating
+    pub fn finalize_into(mut self, output: &mut [u8]) {
+        // Padding
+        self.permutation.xor_byte(&mut self.state, self.offset, self.delimiter);
+        self.permutation.xor_byte(&mut self.state, self.rate - 1, 0x80);
+        self.permutation.permute(&mut self.state);
+
+        // Squeeze
+        for (i, byte) in output.iter_mut().enumerate() {
+            let position = i % self.rate;
+            if i > 0 && position == 0 {
+                self.permutation.permute(&mut self.state);
+            }
+            *byte = self.permutation.get_byte(&self.state, position);
+        }
+    }
+}
+
+impl Drop for KeccakSponge {
+    fn drop(&mut self) {
+        zeroize(&mut self.state);
+    }
+}
+
+// Intermediate values from the Keccak team (XKCP KeccakF-{200,400,800}-IntermediateValues.txt):
+// the all-zero state permuted once, then permuted again.
+
+#[cfg(test)]
+fn assert_two_permutations(width: usize, first: [u64; 25], second: [u64; 25]) {
+    let perm = KeccakF::new(width, RoundConstantMode::Table);
+    let mut state = [0u64; 25];
+    perm.permute(&mut state);
+    assert_eq!(state, first, "Keccak-f[{}] first permutation", width);
+    perm.permute(&mut state);
+    assert_eq!(state, second, "Keccak-f[{}] second permutation", width);
+}
+
+#[test]
+fn test_keccak_f200_intermediate_values() {
+    assert_two_permutations(
+        200,
+        [
+            0x3C, 0x28, 0x26, 0x84, 0x1C, 0xB3, 0x5C, 0x17, 0x1E, 0xAA, 0xE9, 0xB8, 0x11, 0x13,
+            0x4C, 0xEA, 0xA3, 0x85, 0x2C, 0x69, 0xD2, 0xC5, 0xAB, 0xAF, 0xEA,
+        ],
+        [
+            0x1B, 0xEF, 0x68, 0x94, 0x92, 0xA8, 0xA5, 0x43, 0xA5, 0x99, 0x9F, 0xDB, 0x83, 0x4E,
+            0x31, 0x66, 0xA1, 0x4B, 0xE8, 0x27, 0xD9, 0x50, 0x40, 0x47, 0x9E,
+        ],
+    );
+}
+
+#[test]
+fn test_keccak_f400_intermediate_values() {
+    assert_two_permutations(
+        400,
+        [
+            0x09F5, 0x40AC, 0x0FA9, 0x14F5, 0xE89F, 0xECA0, 0x5BD1, 0x7870, 0xEFF0, 0xBF8F, 0x0337,
+            0x6052, 0xDC75, 0x0EC9, 0xE776, 0x5246, 0x59A1, 0x5D81, 0x6D95, 0x6E14, 0x633E, 0x58EE,
+            0x71FF, 0x714C, 0xB38E,
+        ],
+        [
+            0xE537, 0xD5D6, 0xDBE7, 0xAAF3, 0x9BC7, 0xCA7D, 0x86B2, 0xFDEC, 0x692C, 0x4E5B, 0x67B1,
+            0x15AD, 0xA7F7, 0xA66F, 0x67FF, 0x3F8A, 0x2F99, 0xE2C2, 0x656B, 0x5F31, 0x5BA6, 0xCA29,
+            0xC224, 0xB85C, 0x097C,
+        ],
+    );
+}
+
+#[test]
+fn test_keccak_f800_intermediate_values() {
+    assert_two_permutations(
+        800,
+        [
+            0xE531D45D, 0xF404C6FB, 0x23A0BF99, 0xF1F8452F, 0x51FFD042, 0xE539F578, 0xF00B80A7,
+            0xAF973664, 0xBF5AF34C, 0x227A2424, 0x88172715, 0x9F685884, 0xB15CD054, 0x1BF4FC0E,
+            0x6166FA91, 0x1A9E599A, 0xA3970A1F, 0xAB659687, 0xAFAB8D68, 0xE74B1015, 0x34001A98,
+            0x4119EFF3, 0x930A0E76, 0x87B28070, 0x11EFE996,
+        ],
+        [
+            0x75BF2D0D, 0x9B610E89, 0xC826AF40, 0x64CD84AB, 0xF905BDD6, 0xBC832835, 0x5F8001B9,
+            0x15662CCE, 0x8E38C95E, 0x701FE543, 0x1B544380, 0x89ACDEFF, 0x51EDB5DE, 0x0E9702D9,
+            0x6C19AA16, 0xA2913EEE, 0x60754E9A, 0x9819063C, 0xF4709254, 0xD09F9084, 0x772DA259,
+            0x1DB35DF7, 0x5AA60162, 0x358825D5, 0xB3783BAB,
+        ],
+    );
+}
+
+#[test]
+fn test_keccak_f1600_matches_dedicated_permutation() {
+    use crate::KeccakF1600;
+
+    for mode in [RoundConstantMode::Table, RoundConstantMode::Lfsr] {
+        let generic = KeccakF::new(1600, mode);
+        let dedicated = KeccakF1600::new(mode);
+        for rounds in [1, 12, 24, 26] {
+            let mut a = [0u64; 25];
+            for (i, lane) in a.iter_mut().enumerate() {
+                *lane = i as u64 * 0x0123456789abcdef;
+            }
+            let mut b = a;
+            generic.permute_rounds(&mut a, rounds);
+            dedicated.permute_rounds(&mut b, rounds);
+            assert_eq!(a, b, "nr = {}", rounds);
+        }
+    }
+}
+
+#[test]
+fn test_keccak_f_round_counts_and_lane_masks() {
+    let expected_rounds = [(25, 12), (50, 14), (100, 16), (200, 18), (400, 20), (800, 22), (1600, 24)];
+    for (width, rounds) in expected_rounds {
+        let perm = KeccakF::new(width, RoundConstantMode::Lfsr);
+        assert_eq!(perm.rounds(), rounds);
+        assert_eq!(perm.width(), width);
+
+        let mut state = [0u64; 25];
+        state[3] = 1;
+        perm.permute(&mut state);
+        let mask = u64::MAX >> (64 - perm.lane_bits());
+        assert!(state.iter().all(|&lane| lane & !mask == 0), "Keccak-f[{}] leaked outside its lanes", width);
+        assert_ne!(state, [0u64; 25]);
+    }
+}
+
+#[test]
+fn test_keccak_f25_is_injective_on_samples() {
+    // Keccak-f[25] has only 2^25 states; distinct inputs must stay distinct
+    let perm = KeccakF::new(25, RoundConstantMode::Table);
+    let mut outputs = std::collections::HashSet::new();
+    for input in 0u64..4096 {
+        let mut state = [0u64; 25];
+        for (i, lane) in state.iter_mut().enumerate().take(12) {
+            *lane = (input >> i) & 1;
+        }
+        perm.permute(&mut state);
+        assert!(outputs.insert(state), "collision for input {}", input);
+    }
+}
+
+// No official intermediate values for Keccak-f[25], [50] and [100] are vendored
+// here, so those widths are checked against this bit-level transcription of
+// FIPS 202 sections 3.2.1-3.2.5 and 3.3. It shares no code or tables with
+// `KeccakF`: rho offsets come from the (x, y) walk and round constants from
+// the rc(t) LFSR. It is validated on the widths that do have official vectors.
+
+#[cfg(test)]
+fn reference_keccak_f(width: usize, lanes: &mut [u64; 25]) {
+    let w = width / 25;
+    let l = w.trailing_zeros() as usize;
+    let bit = |a: &Vec<Vec<Vec<u8>>>, x: usize, y: usize, z: usize| a[x % 5][y % 5][z % w];
+
+    // A[x][y][z] = bit z of lane x + 5y
+    let mut a = vec![vec![vec![0u8; w]; 5]; 5];
+    for (x, y, z) in (0..5).flat_map(|x| (0..5).flat_map(move |y| (0..w).map(move |z| (x, y, z)))) {
+        a[x][y][z] = ((lanes[x + 5 * y] >> z) & 1) as u8;
+    }
+
+    let rc = |t: usize| -> u8 {
+        if t.is_multiple_of(255) {
+            return 1;
+        }
+        let mut r = [1u8, 0, 0, 0, 0, 0, 0, 0];
+        for _ in 1..=t % 255 {
+            let mut shifted = [0u8; 9];
+            shifted[1..].copy_from_slice(&r);
+            for i in [0, 4, 5, 6] {
+                shifted[i] ^= shifted[8];
+            }
+            r.copy_from_slice(&shifted[..8]);
+        }
+        r[0]
+    };
+
+    for round in 0..12 + 2 * l {
+        // theta
+        let column = |x: usize, z: usize| (0..5).fold(0, |acc, y| acc ^ bit(&a, x, y, z));
+        let d: Vec<Vec<u8>> = (0..5)
+            .map(|x| (0..w).map(|z| column(x + 4, z) ^ column(x + 1, z + w - 1)).collect())
+            .collect();
+        for (x, y, z) in (0..5).flat_map(|x| (0..5).flat_map(move |y| (0..w).map(move |z| (x, y, z)))) {
+            a[x][y][z] ^= d[x][z];
+        }
+
+        // rho
+        let mut rotated = a.clone();
+        let (mut x, mut y) = (1, 0);
+        for t in 0..24 {
+            for z in 0..w {
+                rotated[x][y][z] = a[x][y][(z + w * 64 - (t + 1) * (t + 2) / 2 % (w * 64)) % w];
+            }
+            (x, y) = (y, (2 * x + 3 * y) % 5);
+        }
+
+        // pi
+        let mut permuted = rotated.clone();
+        for (x, y, z) in (0..5).flat_map(|x| (0..5).flat_map(move |y| (0..w).map(move |z| (x, y, z)))) {
+            permuted[x][y][z] = bit(&rotated, x + 3 * y, x, z);
+        }
+
+        // chi
+        for (x, y, z) in (0..5).flat_map(|x| (0..5).flat_map(move |y| (0..w).map(move |z| (x, y, z)))) {
+            a[x][y][z] = permuted[x][y][z] ^ ((bit(&permuted, x + 1, y, z) ^ 1) & bit(&permuted, x + 2, y, z));
+        }
+
+        // iota
+        for j in 0..=l {
+            a[0][0][(1 << j) - 1] ^= rc(j + 7 * round);
+        }
+    }
+
+    for (x, y) in (0..5).flat_map(|x| (0..5).map(move |y| (x, y))) {
+        lanes[x + 5 * y] = (0..w).fold(0, |lane, z| lane | (a[x][y][z] as u64) << z);
+    }
+}
+
+#[test]
+fn test_keccak_f_matches_bit_level_reference() {
+    // The reference first reproduces the official Keccak-f[200/400/800] values
+    // and the dedicated Keccak-f[1600] (covered by the SHA-3 vectors), then is
+    // used as the oracle for the small widths.
+    for width in [1600, 800, 400, 200, 100, 50, 25] {
+        for mode in [RoundConstantMode::Table, RoundConstantMode::Lfsr] {
+            let perm = KeccakF::new(width, mode);
+            let mask = u64::MAX >> (64 - perm.lane_bits());
+            for seed in 1u64..5 {
+                let mut state = [0u64; 25];
+                for (i, lane) in state.iter_mut().enumerate() {
+                    *lane = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15).rotate_left(i as u32 * 7) & mask;
+                }
+                let mut expected = state;
+                reference_keccak_f(width, &mut expected);
+                perm.permute(&mut state);
+                assert_eq!(state, expected, "Keccak-f[{}] seed {}", width, seed);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_bit_level_reference_reproduces_official_values() {
+    let mut state = [0u64; 25];
+    reference_keccak_f(200, &mut state);
+    assert_eq!(state[..3], [0x3C, 0x28, 0x26]);
+    let mut state = [0u64; 25];
+    reference_keccak_f(800, &mut state);
+    assert_eq!(state[24], 0x11EFE996);
+}
+
+#[test]
+#[should_panic]
+fn test_keccak_f_rejects_invalid_width() {
+    KeccakF::new(300, RoundConstantMode::Table);
+}
+
+// Small-state sponges; test_keccak_sponge_matches_rustcrypto checks the same
+// construction against the permutations of the RustCrypto `keccak` crate.
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_keccak_sponge_f200() {
+    let mut sponge = KeccakSponge::new(200, 16, 0x06);
+    sponge.update(b"abc");
+    assert_eq!(
+        hex::encode(sponge.finalize(40)),
+        "c2c5e4bb429a02bf1eb774cb6b82ab4f884e1967751a7cfdd069725378af44160abcd6a3447e52d6"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_keccak_sponge_f400() {
+    let mut sponge = KeccakSponge::new(400, 34, 0x06);
+    sponge.update(b"abc");
+    assert_eq!(
+        hex::encode(sponge.finalize(40)),
+        "dac60abecd32879993a7b7122c05c124aca077a928fc4a3668041d00f2839f6a96db19fc6e84399e"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_keccak_sponge_f800() {
+    let mut sponge = KeccakSponge::new(800, 68, 0x06);
+    for chunk in [0xA5u8; 150].chunks(11) {
+        sponge.update(chunk);
+    }
+    assert_eq!(
+        hex::encode(sponge.finalize(100)),
+        "15176d16300e4866ba34a1ffd7f06feeef51698d3b98183a961789d0fb510e8a6da2067fbaf7c85cdd5fe9f93bf1c5e9681bfcaae77b07b5b68c9e2e53706b8475d0eaa16d7afda431122da3f6d14df193f8e8424ff86fc374d347956ee0c6b7fed517d2"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_keccak_sponge_f1600_is_sha3() {
+    let mut sponge = KeccakSponge::new(1600, 136, 0x06);
+    sponge.update(b"abc");
+    assert_eq!(sponge.finalize(32), crate::sha3_256(b"abc").to_vec());
+}
+
+/// Byte-oriented sponge with pad10*1 on the RustCrypto `keccak` permutations,
+/// lanes stored little-endian as in FIPS 202
+#[cfg(all(test, feature = "alloc"))]
+fn rustcrypto_sponge(width: usize, rate: usize, delimiter: u8, message: &[u8], output_len: usize) -> Vec<u8> {
+    fn permute(width: usize, state: &mut [u8]) {
+        match width {
+            200 => {
+                let mut lanes = [0u8; 25];
+                lanes.copy_from_slice(state);
+                keccak::f200(&mut lanes);
+                state.copy_from_slice(&lanes);
+            }
+            400 => {
+                let mut lanes = [0u16; 25];
+                for (lane, bytes) in lanes.iter_mut().zip(state.chunks(2)) {
+                    *lane = u16::from_le_bytes(bytes.try_into().unwrap());
+                }
+                keccak::f400(&mut lanes);
+                for (lane, bytes) in lanes.iter().zip(state.chunks_mut(2)) {
+                    bytes.copy_from_slice(&lane.to_le_bytes());
+                }
+            }
+            800 => {
+                let mut lanes = [0u32; 25];
+                for (lane, bytes) in lanes.iter_mut().zip(state.chunks(4)) {
+                    *lane = u32::from_le_bytes(bytes.try_into().unwrap());
+                }
+                keccak::f800(&mut lanes);
+                for (lane, bytes) in lanes.iter().zip(state.chunks_mut(4)) {
+                    bytes.copy_from_slice(&lane.to_le_bytes());
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    let mut padded = message.to_vec();
+    padded.push(delimiter);
+    padded.resize(padded.len().div_ceil(rate) * rate, 0);
+    let last = padded.len() - 1;
+    padded[last] ^= 0x80;
+
+    let mut state = vec![0u8; width / 8];
+    for block in padded.chunks(rate) {
+        for (s, b) in state.iter_mut().zip(block) {
+            *s ^= b;
+        }
+        permute(width, &mut state);
+    }
+    let mut output = Vec::with_capacity(output_len);
+    loop {
+        let take = rate.min(output_len - output.len());
+        output.extend_from_slice(&state[..take]);
+        if output.len() == output_len {
+            return output;
+        }
+        permute(width, &mut state);
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_keccak_sponge_matches_rustcrypto() {
+    for (width, rate) in [(200, 16), (200, 8), (400, 34), (400, 18), (800, 68), (800, 40)] {
+        for len in [0, 1, rate - 1, rate, rate + 1, 3 * rate + 5] {
+            let message: Vec<u8> = (0..len).map(|i| (i * 29 + 7) as u8).collect();
+            for delimiter in [0x01, 0x06, 0x1F] {
+                let mut sponge = KeccakSponge::new(width, rate, delimiter);
+                sponge.update(&message);
+                assert_eq!(
+                    sponge.finalize(3 * rate + 1),
+                    rustcrypto_sponge(width, rate, delimiter, &message, 3 * rate + 1),
+                    "b = {}, rate = {}, len = {}, delimiter = {:#04x}",
+                    width,
+                    rate,
+                    len,
+                    delimiter
+                );
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "first padding bit")]
+fn test_keccak_sponge_zero_delimiter_panics() {
+    KeccakSponge::new(400, 34, 0x00);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_keccak_sponge_finalize_into() {
+    let mut sponge = KeccakSponge::new(400, 34, 0x06);
+    sponge.update(b"abc");
+    let mut output = [0u8; 40];
+    sponge.clone().finalize_into(&mut output);
+    assert_eq!(output.to_vec(), sponge.finalize(40));
+}
@@ -1,7 +1,7 @@
-use crate::round_constants::{get_round_constant, RoundConstantMode};
+use crate::round_constants::{get_round_constant, round_index, RoundConstantMode};
 
 // Rotation offsets (x, y)
-const RHO: [[u32; 5]; 5] = [
+pub(crate) const RHO: [[u32; 5]; 5] = [
     [0, 36, 3, 41, 18],
     [1, 44, 10, 45, 2],
     [62, 6, 43, 15, 61],
@@ -86,4 +86,12 @@ impl KeccakF1600 {
             self.round(state, round);
         }
     }
+
+    /// Keccak-p[1600, nr]: the last `rounds` rounds of Keccak-f[1600]
+    /// (or, for nr > 24, extra rounds prepended with the FIPS 202 indexing)
+    pub fn permute_rounds(&self, state: &mut [u64; 25], rounds: usize) {
+        for i in 0..rounds {
+            self.round(state, round_index(i, rounds));
+        }
+    }
 }
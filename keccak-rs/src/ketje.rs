@@ -0,0 +1,347 @@
+use alloc::{vec, vec::Vec};
+use crate::ct::{constant_time_eq, zeroize};
+use crate::error::AuthenticationError;
+use crate::keccak_f::KeccakF;
+use crate::round_constants::RoundConstantMode;
+
+/// Rounds of Keccak-p* for start, step and stride calls of the MonkeyDuplex
+const START_ROUNDS: usize = 12;
+const STEP_ROUNDS: usize = 1;
+const STRIDE_ROUNDS: usize = 6;
+
+/// Default tag length (128 bits)
+pub const KETJE_TAG_LEN: usize = 16;
+
+/// Frame bits followed by the first padding bit, as in the Ketje specification:
+/// "00", "01", "11" and "10" frame the blocks of MonkeyWrap, a single "0" the tag extension
+const FRAME_00: u8 = 0x04;
+const FRAME_01: u8 = 0x06;
+const FRAME_11: u8 = 0x07;
+const FRAME_10: u8 = 0x05;
+const FRAME_0: u8 = 0x02;
+
+/// The π step mapping on lane positions: A'[x, y] = A[(x + 3y) mod 5, x]
+fn pi(state: &mut [u64; 25]) {
+    let old = *state;
+    for y in 0..5 {
+        for x in 0..5 {
+            state[x + 5 * y] = old[(x + 3 * y) % 5 + 5 * x];
+        }
+    }
+}
+
+/// Inverse of [`pi`]: A[x, y] = A'[y, 2(x - y) mod 5]
+fn pi_inverse(state: &mut [u64; 25]) {
+    let old = *state;
+    for y in 0..5 {
+        for x in 0..5 {
+            state[x + 5 * y] = old[y + 5 * ((2 * (5 + x - y)) % 5)];
+        }
+    }
+}
+
+/// The MonkeyDuplex on the twisted permutation Keccak-p*[b] = π ∘ Keccak-p[b] ∘ π⁻¹.
+///
+/// The state is kept in the twisted representation, so the outer bytes are the
+/// first bytes of the state after π.
+#[derive(Clone)]
+struct MonkeyDuplex {
+    permutation: KeccakF,
+    state: [u64; 25],
+    rate: usize,   // rho in bytes; the duplex rate is rho + 4 bits
+}
+
+impl MonkeyDuplex {
+    fn new(width: usize, rate: usize) -> Self {
+        MonkeyDuplex {
+            permutation: KeccakF::new(width, RoundConstantMode::Table),
+            state: [0; 25],
+            rate,
+        }
+    }
+
+    fn permute(&mut self, rounds: usize) {
+        pi_inverse(&mut self.state);
+        self.permutation.permute_rounds(&mut self.state, rounds);
+        pi(&mut self.state);
+    }
+
+    /// start(I): the state becomes I || pad10*1 over the full width, then Keccak-p*[b, 12]
+    fn start(&mut self, input: &[u8]) {
+        let state_bytes = self.permutation.width() / 8;
+        self.state = [0; 25];
+        for (i, &byte) in input.iter().enumerate() {
+            self.permutation.xor_byte(&mut self.state, i, byte);
+        }
+        self.permutation.xor_byte(&mut self.state, input.len(), 0x01);
+        self.permutation.xor_byte(&mut self.state, state_bytes - 1, 0x80);
+        self.permute(START_ROUNDS);
+    }
+
+    /// step/stride(σ, ℓ): absorbs a block of at most rho bits with its frame bits and pad10*1
+    /// over rho + 4 bits, permutes, then fills `output` (at most rho bits)
+    fn duplexing(&mut self, input: &[u8], frame: u8, output: &mut [u8], rounds: usize) {
+        debug_assert!(input.len() <= self.rate && output.len() <= self.rate);
+        for (i, &byte) in input.iter().enumerate() {
+            self.permutation.xor_byte(&mut self.state, i, byte);
+        }
+        self.permutation.xor_byte(&mut self.state, input.len(), frame);
+        self.permutation.xor_byte(&mut self.state, self.rate, 0x08);
+        self.permute(rounds);
+        for (i, byte) in output.iter_mut().enumerate() {
+            *byte = self.permutation.get_byte(&self.state, i);
+        }
+    }
+
+    /// MonkeyWrap.Wrap (or unwrap): always at least one, possibly empty, block of
+    /// associated data and of message; the fed-back message blocks are plaintext
+    fn wrap(&mut self, associated_data: &[u8], input: &[u8], output: &mut [u8], tag: &mut [u8], decrypting: bool) {
+        let rate = self.rate;
+
+        let mut header = associated_data.chunks(rate).peekable();
+        let mut last_header: &[u8] = &[];
+        while let Some(block) = header.next() {
+            if header.peek().is_some() {
+                self.duplexing(block, FRAME_00, &mut [], STEP_ROUNDS);
+            } else {
+                last_header = block;
+            }
+        }
+
+        let mut keystream = vec![0u8; rate];
+        let first_len = core::cmp::min(input.len(), rate);
+        self.duplexing(last_header, FRAME_01, &mut keystream[..first_len], STEP_ROUNDS);
+
+        let mut offset = 0;
+        let mut plain_block = vec![0u8; rate];
+        loop {
+            let len = core::cmp::min(input.len() - offset, rate);
+            for i in 0..len {
+                output[offset + i] = input[offset + i] ^ keystream[i];
+                plain_block[i] = if decrypting { output[offset + i] } else { input[offset + i] };
+            }
+            offset += len;
+
+            if offset == input.len() {
+                // The tag comes from a stride, extended by steps on empty blocks if needed
+                let first_tag_len = core::cmp::min(tag.len(), rate);
+                let (head, mut rest) = tag.split_at_mut(first_tag_len);
+                self.duplexing(&plain_block[..len], FRAME_10, head, STRIDE_ROUNDS);
+                while !rest.is_empty() {
+                    let n = core::cmp::min(rest.len(), rate);
+                    let (chunk, tail) = rest.split_at_mut(n);
+                    self.duplexing(&[], FRAME_0, chunk, STEP_ROUNDS);
+                    rest = tail;
+                }
+                zeroize(&mut plain_block);
+                zeroize(&mut keystream);
+                return;
+            }
+            let next_len = core::cmp::min(input.len() - offset, rate);
+            self.duplexing(&plain_block[..len], FRAME_11, &mut keystream[..next_len], STEP_ROUNDS);
+        }
+    }
+}
+
+impl Drop for MonkeyDuplex {
+    fn drop(&mut self) {
+        zeroize(&mut self.state);
+    }
+}
+
+macro_rules! ketje {
+    ($(#[$doc:meta])* $name:ident, $width:expr, $rate:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name {
+            duplex: MonkeyDuplex,
+            failed: bool,
+        }
+
+        impl $name {
+            /// Starts the MonkeyDuplex with keypack(key, |key| + 16) || nonce, which
+            /// must leave room for the padding in the state
+            pub fn new(key: &[u8], nonce: &[u8]) -> Self {
+                assert!(
+                    key.len() + 2 + nonce.len() < $width / 8,
+                    concat!(stringify!($name), " key and nonce are too long for the state")
+                );
+
+                let mut start = Vec::with_capacity(key.len() + 2 + nonce.len());
+                start.push((key.len() + 2) as u8);
+                start.extend_from_slice(key);
+                start.push(0x01);
+                start.extend_from_slice(nonce);
+
+                let mut duplex = MonkeyDuplex::new($width, $rate);
+                duplex.start(&start);
+                zeroize(&mut start);
+                $name { duplex, failed: false }
+            }
+
+            /// Encrypts `plaintext` and authenticates it together with `associated_data`.
+            /// Returns the ciphertext followed by a [`KETJE_TAG_LEN`]-byte tag.
+            pub fn encrypt(&mut self, associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+                assert!(!self.failed, concat!(stringify!($name), " session was closed by a failed decryption"));
+
+                let mut output = vec![0u8; plaintext.len() + KETJE_TAG_LEN];
+                let (ciphertext, tag) = output.split_at_mut(plaintext.len());
+                self.duplex.wrap(associated_data, plaintext, ciphertext, tag, false);
+                output
+            }
+
+            /// Decrypts a ciphertext produced by [`encrypt`](Self::encrypt). On failure
+            /// no plaintext is released and the session refuses any further use.
+            pub fn decrypt(&mut self, associated_data: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, AuthenticationError> {
+                assert!(!self.failed, concat!(stringify!($name), " session was closed by a failed decryption"));
+                if ciphertext.len() < KETJE_TAG_LEN {
+                    self.failed = true;
+                    return Err(AuthenticationError);
+                }
+                let (ciphertext, received_tag) = ciphertext.split_at(ciphertext.len() - KETJE_TAG_LEN);
+
+                let mut plaintext = vec![0u8; ciphertext.len()];
+                let mut tag = [0u8; KETJE_TAG_LEN];
+                self.duplex.wrap(associated_data, ciphertext, &mut plaintext, &mut tag, true);
+
+                if constant_time_eq(&tag, received_tag) {
+                    Ok(plaintext)
+                } else {
+                    zeroize(&mut plaintext);
+                    self.failed = true;
+                    Err(AuthenticationError)
+                }
+            }
+        }
+    };
+}
+
+ketje!(
+    /// Ketje Jr: MonkeyWrap on Keccak-p*[200] with a 16-bit rate.
+    ///
+    /// Meant for very small devices; the 200-bit state limits key plus nonce
+    /// to 22 bytes (e.g. a 96-bit key and a 10-byte nonce). An instance is a
+    /// session, successive messages must be decrypted in order.
+    KetjeJr, 200, 2
+);
+ketje!(
+    /// Ketje Sr: MonkeyWrap on Keccak-p*[400] with a 32-bit rate.
+    ///
+    /// Key plus nonce are limited to 47 bytes (e.g. a 128-bit key and a
+    /// 31-byte nonce). An instance is a session, successive messages must be
+    /// decrypted in order.
+    KetjeSr, 400, 4
+);
+
+#[test]
+fn test_ketje_pi_round_trip() {
+    let original: [u64; 25] = core::array::from_fn(|i| i as u64);
+    let mut state = original;
+    pi(&mut state);
+    // A'[0, 1] = A[3, 0] and A'[1, 0] = A[1, 1]
+    assert_eq!(state[5], 3);
+    assert_eq!(state[1], 6);
+    pi_inverse(&mut state);
+    assert_eq!(state, original);
+}
+
+#[test]
+fn test_ketje_twisted_permutation_is_conjugate() {
+    // Keccak-p* applied to π(A) is π of Keccak-p applied to A
+    let permutation = KeccakF::new(400, RoundConstantMode::Table);
+    let mut plain: [u64; 25] = core::array::from_fn(|i| (i as u64 * 0x9E37) & 0xFFFF);
+    let mut duplex = MonkeyDuplex::new(400, 4);
+    duplex.state = plain;
+    pi(&mut duplex.state);
+
+    duplex.permute(STRIDE_ROUNDS);
+    permutation.permute_rounds(&mut plain, STRIDE_ROUNDS);
+    pi(&mut plain);
+    assert_eq!(duplex.state, plain);
+}
+
+#[cfg(test)]
+use crate::spongewrap::assert_rejects_tampering;
+
+#[cfg(test)]
+const TEST_KEY: [u8; 12] = *b"Ketje tests!";
+
+#[test]
+fn test_ketje_round_trip() {
+    for len in [0usize, 1, 2, 3, 4, 5, 17, 100] {
+        let plaintext: Vec<u8> = (0..len).map(|i| (i * 13 + 1) as u8).collect();
+        for ad_len in [0usize, 1, 2, 4, 9] {
+            let ad = vec![0x5Au8; ad_len];
+
+            let ciphertext = KetjeJr::new(&TEST_KEY, b"nonce").encrypt(&ad, &plaintext);
+            let decrypted = KetjeJr::new(&TEST_KEY, b"nonce").decrypt(&ad, &ciphertext);
+            assert_eq!(decrypted, Ok(plaintext.clone()), "Jr: len = {}, ad_len = {}", len, ad_len);
+
+            let ciphertext = KetjeSr::new(&TEST_KEY, b"nonce").encrypt(&ad, &plaintext);
+            let decrypted = KetjeSr::new(&TEST_KEY, b"nonce").decrypt(&ad, &ciphertext);
+            assert_eq!(decrypted, Ok(plaintext.clone()), "Sr: len = {}, ad_len = {}", len, ad_len);
+        }
+    }
+}
+
+#[test]
+fn test_ketje_tamper_detection() {
+    let ciphertext = KetjeJr::new(&TEST_KEY, b"nonce").encrypt(b"hdr", b"sensor reading");
+    assert_rejects_tampering(&TEST_KEY, b"nonce", b"hdr", &ciphertext, |key, nonce, ad, ct| {
+        KetjeJr::new(key, nonce).decrypt(ad, ct)
+    });
+
+    let ciphertext = KetjeSr::new(&TEST_KEY, b"nonce").encrypt(b"hdr", b"sensor reading");
+    assert_rejects_tampering(&TEST_KEY, b"nonce", b"hdr", &ciphertext, |key, nonce, ad, ct| {
+        KetjeSr::new(key, nonce).decrypt(ad, ct)
+    });
+}
+
+#[test]
+fn test_ketje_session() {
+    let mut sender = KetjeSr::new(&TEST_KEY, b"session");
+    let first = sender.encrypt(b"1", b"first");
+    let second = sender.encrypt(b"2", b"second");
+
+    let mut receiver = KetjeSr::new(&TEST_KEY, b"session");
+    assert_eq!(receiver.decrypt(b"1", &first).unwrap(), b"first");
+    assert_eq!(receiver.decrypt(b"2", &second).unwrap(), b"second");
+    assert!(KetjeSr::new(&TEST_KEY, b"session").decrypt(b"2", &second).is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_ketje_session_closed_after_failure() {
+    let mut receiver = KetjeSr::new(&TEST_KEY, b"nonce");
+    assert!(receiver.decrypt(b"", &[0u8; KETJE_TAG_LEN]).is_err());
+    receiver.encrypt(b"", b"must not be wrapped");
+}
+
+#[test]
+#[should_panic]
+fn test_ketje_decrypt_refused_after_failure() {
+    let mut receiver = KetjeJr::new(&TEST_KEY, b"nonce");
+    let ciphertext = KetjeJr::new(&TEST_KEY, b"nonce").encrypt(b"", b"reading");
+    assert!(receiver.decrypt(b"", &ciphertext[1..]).is_err());
+    let _ = receiver.decrypt(b"", &ciphertext);
+}
+
+#[test]
+#[should_panic]
+fn test_ketje_jr_rejects_long_nonce() {
+    KetjeJr::new(&TEST_KEY, b"eleven byte");
+}
+
+#[test]
+fn test_ketje_jr_regression_vectors() {
+    let mut ketje = KetjeJr::new(&TEST_KEY, b"");
+    assert_eq!(hex::encode(ketje.encrypt(b"", b"")), "80cd92cb747d23260c4fe6ae5ff6123a");
+    assert_eq!(hex::encode(ketje.encrypt(b"associated", b"plaintext")), "c37b31350a1d95c1531233a16e73a36a277c6f5588d2620bbf");
+}
+
+#[test]
+fn test_ketje_sr_regression_vectors() {
+    let mut ketje = KetjeSr::new(&TEST_KEY, b"");
+    assert_eq!(hex::encode(ketje.encrypt(b"", b"")), "d7bb56f3723de28504b2d4c04eb2d9a6");
+    assert_eq!(hex::encode(ketje.encrypt(b"associated", b"plaintext")), "d8ee5c92739ac9b9dd87f9be5d4239fcceb49b4ff20d08cdf2");
+}
@@ -0,0 +1,378 @@
+use alloc::{vec, vec::Vec};
+use crate::ct::{constant_time_eq, zeroize};
+use crate::error::AuthenticationError;
+use crate::keccak_f::KeccakF;
+use crate::round_constants::RoundConstantMode;
+
+/// Keyak uses Keccak-p[b, 12] for every width
+const ROUNDS: usize = 12;
+
+/// Capacity c in bits, shared by all Keyak instances
+const CAPACITY_BITS: usize = 256;
+
+/// Length of the key pack that starts the SUV: enc8(40) || K || pad10*
+const KEY_PACK_LEN: usize = 40;
+
+/// Length of the authentication tag (tau = 128 bits)
+pub const KEYAK_TAG_LEN: usize = 16;
+
+/// A piston: one Keccak-p[b, 12] state with the Motorist byte layout.
+///
+/// The first Rs bytes carry keystream, up to Ra bytes take injected metadata,
+/// and the four bytes right after Ra hold the EOM, CryptEnd, InjectStart and
+/// InjectEnd fields that frame every call.
+#[derive(Clone)]
+struct Piston {
+    permutation: KeccakF,
+    state: [u64; 25],
+    squeeze_rate: usize,   // Rs in bytes
+    absorb_rate: usize,    // Ra in bytes
+}
+
+impl Piston {
+    fn new(width: usize) -> Self {
+        let permutation = KeccakF::new(width, RoundConstantMode::Table);
+        // W = max(b/25, 8), Rs = W * floor((b - max(c, 32)) / W), Ra = W * floor((b - 32) / W)
+        let word_bits = core::cmp::max(width / 25, 8);
+        let squeeze_bits = (width - core::cmp::max(CAPACITY_BITS, 32)) / word_bits * word_bits;
+        let absorb_bits = (width - 32) / word_bits * word_bits;
+        Piston {
+            permutation,
+            state: [0; 25],
+            squeeze_rate: squeeze_bits / 8,
+            absorb_rate: absorb_bits / 8,
+        }
+    }
+
+    fn eom(&self) -> usize {
+        self.absorb_rate
+    }
+
+    fn crypt_end(&self) -> usize {
+        self.absorb_rate + 1
+    }
+
+    fn inject_start(&self) -> usize {
+        self.absorb_rate + 2
+    }
+
+    fn inject_end(&self) -> usize {
+        self.absorb_rate + 3
+    }
+
+    fn xor_byte(&mut self, index: usize, byte: u8) {
+        self.permutation.xor_byte(&mut self.state, index, byte);
+    }
+
+    fn get_byte(&self, index: usize) -> u8 {
+        self.permutation.get_byte(&self.state, index)
+    }
+
+    /// Encrypts (or decrypts) input from state offset `offset` up to Rs; returns the bytes consumed
+    fn crypt(&mut self, input: &[u8], output: &mut [u8], offset: usize, unwrap: bool) -> usize {
+        let len = core::cmp::min(input.len(), self.squeeze_rate - offset);
+        for i in 0..len {
+            let key_byte = self.get_byte(offset + i);
+            output[i] = input[i] ^ key_byte;
+            // The state always ends up holding the ciphertext byte
+            let ciphertext = if unwrap { input[i] } else { output[i] };
+            self.xor_byte(offset + i, key_byte ^ ciphertext);
+        }
+        self.xor_byte(self.crypt_end(), (offset + len) as u8);
+        len
+    }
+
+    /// XORs metadata from offset 0 (or Rs when crypting) up to Ra; returns the bytes consumed
+    fn inject(&mut self, data: &[u8], crypting: bool) -> usize {
+        let start = if crypting { self.squeeze_rate } else { 0 };
+        let len = core::cmp::min(data.len(), self.absorb_rate - start);
+        self.xor_byte(self.inject_start(), start as u8);
+        for (i, &byte) in data[..len].iter().enumerate() {
+            self.xor_byte(start + i, byte);
+        }
+        self.xor_byte(self.inject_end(), (start + len) as u8);
+        len
+    }
+
+    fn spark(&mut self, end_of_message: bool, tag_len: usize) {
+        if end_of_message {
+            self.xor_byte(self.eom(), if tag_len == 0 { 0xFF } else { tag_len as u8 });
+        }
+        self.permutation.permute_rounds(&mut self.state, ROUNDS);
+    }
+}
+
+impl Drop for Piston {
+    fn drop(&mut self) {
+        zeroize(&mut self.state);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Fresh,
+    Crypted,
+    EndOfCrypt,
+    EndOfMessage,
+}
+
+/// The Motorist mode (engine and session layer) with a single piston (Pi = 1)
+#[derive(Clone)]
+struct Motorist {
+    piston: Piston,
+    phase: Phase,
+    tag_offset: usize,   // Et: length of the last tag, where the next keystream starts
+    failed: bool,
+}
+
+impl Motorist {
+    fn new(width: usize) -> Self {
+        Motorist {
+            piston: Piston::new(width),
+            phase: Phase::Fresh,
+            tag_offset: 0,
+            failed: false,
+        }
+    }
+
+    fn spark(&mut self, end_of_message: bool, tag_len: usize) {
+        self.piston.spark(end_of_message, tag_len);
+        self.tag_offset = tag_len;
+    }
+
+    fn crypt(&mut self, input: &[u8], output: &mut [u8], unwrap: bool) -> usize {
+        let len = self.piston.crypt(input, output, self.tag_offset, unwrap);
+        self.phase = if len < input.len() { Phase::Crypted } else { Phase::EndOfCrypt };
+        len
+    }
+
+    fn inject(&mut self, data: &[u8]) -> usize {
+        let crypting = matches!(self.phase, Phase::Crypted | Phase::EndOfCrypt);
+        let len = self.piston.inject(data, crypting);
+        if self.phase == Phase::Crypted || len < data.len() {
+            self.spark(false, 0);
+            self.phase = Phase::Fresh;
+        } else {
+            self.phase = Phase::EndOfMessage;
+        }
+        len
+    }
+
+    fn inject_collective(&mut self, data: &[u8], diversify: bool) {
+        // Sized up front so that the copy of the key is never reallocated unwiped
+        let mut data_copy = Vec::with_capacity(data.len() + 2);
+        data_copy.extend_from_slice(data);
+        if diversify {
+            // enc8(Pi) || enc8(i) for the single piston
+            data_copy.extend_from_slice(&[1, 0]);
+        }
+
+        let mut offset = 0;
+        loop {
+            offset += self.piston.inject(&data_copy[offset..], false);
+            if offset == data_copy.len() {
+                break;
+            }
+            self.spark(false, 0);
+            self.phase = Phase::Fresh;
+        }
+        self.phase = Phase::EndOfMessage;
+        zeroize(&mut data_copy);
+    }
+
+    fn get_tag(&mut self, tag: &mut [u8]) {
+        debug_assert!(self.phase == Phase::EndOfMessage);
+        self.spark(true, tag.len());
+        for (i, byte) in tag.iter_mut().enumerate() {
+            *byte = self.piston.get_byte(i);
+        }
+        self.phase = Phase::Fresh;
+    }
+
+    /// StartEngine without a tag and without forgetting: inject the SUV, then close it
+    fn start_engine(&mut self, suv: &[u8]) {
+        self.inject_collective(suv, true);
+        self.get_tag(&mut []);
+    }
+
+    /// Wrap (or unwrap) one message with its metadata and produce its tag
+    fn wrap(&mut self, input: &[u8], output: &mut [u8], metadata: &[u8], tag: &mut [u8], unwrap: bool) {
+        let mut input_offset = 0;
+        let mut metadata_offset = 0;
+        if input.is_empty() {
+            metadata_offset += self.inject(metadata);
+        }
+        while input_offset < input.len() {
+            input_offset += self.crypt(&input[input_offset..], &mut output[input_offset..], unwrap);
+            metadata_offset += self.inject(&metadata[metadata_offset..]);
+        }
+        while metadata_offset < metadata.len() {
+            metadata_offset += self.inject(&metadata[metadata_offset..]);
+        }
+        self.get_tag(tag);
+    }
+}
+
+macro_rules! keyak {
+    ($(#[$doc:meta])* $name:ident, $width:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name {
+            motorist: Motorist,
+        }
+
+        impl $name {
+            /// Starts the engine with SUV = keypack(key, 320) || nonce. Keys are at most 38 bytes.
+            pub fn new(key: &[u8], nonce: &[u8]) -> Self {
+                assert!(key.len() <= KEY_PACK_LEN - 2, "Keyak key must be at most 38 bytes");
+
+                let mut suv = Vec::with_capacity(KEY_PACK_LEN + nonce.len());
+                suv.resize(KEY_PACK_LEN, 0);
+                suv[0] = KEY_PACK_LEN as u8;
+                suv[1..1 + key.len()].copy_from_slice(key);
+                suv[1 + key.len()] = 0x01;
+                suv.extend_from_slice(nonce);
+
+                let mut motorist = Motorist::new($width);
+                motorist.start_engine(&suv);
+                zeroize(&mut suv);
+                $name { motorist }
+            }
+
+            /// Wraps `plaintext` with `associated_data` as metadata. Returns the
+            /// ciphertext followed by a [`KEYAK_TAG_LEN`]-byte tag.
+            pub fn encrypt(&mut self, associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+                assert!(!self.motorist.failed, "Keyak session was closed by a failed decryption");
+
+                let mut output = vec![0u8; plaintext.len() + KEYAK_TAG_LEN];
+                let (ciphertext, tag) = output.split_at_mut(plaintext.len());
+                self.motorist.wrap(plaintext, ciphertext, associated_data, tag, false);
+                output
+            }
+
+            /// Unwraps a ciphertext produced by [`encrypt`](Self::encrypt). On failure
+            /// no plaintext is released and the session refuses any further use.
+            pub fn decrypt(&mut self, associated_data: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, AuthenticationError> {
+                assert!(!self.motorist.failed, "Keyak session was closed by a failed decryption");
+                if ciphertext.len() < KEYAK_TAG_LEN {
+                    self.motorist.failed = true;
+                    return Err(AuthenticationError);
+                }
+                let (ciphertext, received_tag) = ciphertext.split_at(ciphertext.len() - KEYAK_TAG_LEN);
+
+                let mut plaintext = vec![0u8; ciphertext.len()];
+                let mut tag = [0u8; KEYAK_TAG_LEN];
+                self.motorist.wrap(ciphertext, &mut plaintext, associated_data, &mut tag, true);
+
+                if constant_time_eq(&tag, received_tag) {
+                    Ok(plaintext)
+                } else {
+                    zeroize(&mut plaintext);
+                    self.motorist.failed = true;
+                    Err(AuthenticationError)
+                }
+            }
+        }
+    };
+}
+
+keyak!(
+    /// River Keyak: the Motorist mode on Keccak-p[800, 12] with one piston
+    /// (Rs = 68, Ra = 96 bytes), as in the CAESAR submission Keyak v2.
+    ///
+    /// An instance is a session: each `encrypt` authenticates every message
+    /// wrapped before it, and the receiver must `decrypt` in the same order.
+    RiverKeyak, 800
+);
+keyak!(
+    /// Lake Keyak: the Motorist mode on Keccak-p[1600, 12] with one piston
+    /// (Rs = 168, Ra = 192 bytes), as in the CAESAR submission Keyak v2.
+    ///
+    /// An instance is a session: each `encrypt` authenticates every message
+    /// wrapped before it, and the receiver must `decrypt` in the same order.
+    LakeKeyak, 1600
+);
+
+#[cfg(test)]
+use crate::spongewrap::assert_rejects_tampering;
+
+#[cfg(test)]
+const TEST_KEY: [u8; 16] = *b"Keyak test key!!";
+
+#[test]
+fn test_keyak_rates() {
+    let river = Piston::new(800);
+    assert_eq!((river.squeeze_rate, river.absorb_rate), (68, 96));
+    let lake = Piston::new(1600);
+    assert_eq!((lake.squeeze_rate, lake.absorb_rate), (168, 192));
+}
+
+#[test]
+fn test_keyak_round_trip() {
+    for len in [0usize, 1, 51, 52, 67, 68, 69, 168, 169, 500] {
+        let plaintext: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+        for ad_len in [0usize, 1, 24, 28, 96, 97, 300] {
+            let ad: Vec<u8> = (0..ad_len).map(|i| (i * 5 + 1) as u8).collect();
+
+            let ciphertext = RiverKeyak::new(&TEST_KEY, b"nonce").encrypt(&ad, &plaintext);
+            let decrypted = RiverKeyak::new(&TEST_KEY, b"nonce").decrypt(&ad, &ciphertext);
+            assert_eq!(decrypted, Ok(plaintext.clone()), "River: len = {}, ad_len = {}", len, ad_len);
+
+            let ciphertext = LakeKeyak::new(&TEST_KEY, b"nonce").encrypt(&ad, &plaintext);
+            let decrypted = LakeKeyak::new(&TEST_KEY, b"nonce").decrypt(&ad, &ciphertext);
+            assert_eq!(decrypted, Ok(plaintext.clone()), "Lake: len = {}, ad_len = {}", len, ad_len);
+        }
+    }
+}
+
+#[test]
+fn test_keyak_tamper_detection() {
+    let ciphertext = LakeKeyak::new(&TEST_KEY, b"nonce").encrypt(b"header", b"attack at dawn");
+    assert_rejects_tampering(&TEST_KEY, b"nonce", b"header", &ciphertext, |key, nonce, ad, ct| {
+        LakeKeyak::new(key, nonce).decrypt(ad, ct)
+    });
+
+    let ciphertext = RiverKeyak::new(&TEST_KEY, b"nonce").encrypt(b"header", b"attack at dawn");
+    assert_rejects_tampering(&TEST_KEY, b"nonce", b"header", &ciphertext, |key, nonce, ad, ct| {
+        RiverKeyak::new(key, nonce).decrypt(ad, ct)
+    });
+}
+
+#[test]
+fn test_keyak_session() {
+    let mut sender = RiverKeyak::new(&TEST_KEY, b"session nonce");
+    let messages: Vec<Vec<u8>> = (0..5).map(|i| sender.encrypt(&[i], &vec![i; 100 * i as usize])).collect();
+
+    let mut receiver = RiverKeyak::new(&TEST_KEY, b"session nonce");
+    for (i, message) in messages.iter().enumerate() {
+        assert_eq!(receiver.decrypt(&[i as u8], message).unwrap(), vec![i as u8; 100 * i]);
+    }
+
+    // A message only verifies at its position in the session
+    let mut receiver = RiverKeyak::new(&TEST_KEY, b"session nonce");
+    assert!(receiver.decrypt(&[1], &messages[1]).is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_keyak_session_closed_after_failure() {
+    let mut receiver = LakeKeyak::new(&TEST_KEY, b"nonce");
+    assert!(receiver.decrypt(b"", &[0u8; KEYAK_TAG_LEN]).is_err());
+    receiver.encrypt(b"", b"must not be wrapped");
+}
+
+#[test]
+fn test_river_keyak_regression_vectors() {
+    let mut keyak = RiverKeyak::new(&TEST_KEY, b"");
+    assert_eq!(hex::encode(keyak.encrypt(b"", b"")), "05195bf67a2f8165ee20c051b2244fc9");
+    let plaintext: Vec<u8> = (0..100u8).collect();
+    assert_eq!(hex::encode(keyak.encrypt(b"associated data", &plaintext)), "ad018d56d5073a089f149f6c276284302c994768ea85307fdfc9080c351469dee6e6b3deddbb527d7850596f1054135b913af7916debf52ba40955f2f0738ab3576a424b76730d08cfcc2f92fcf7e12548bcb71fa10b51bd2fe00d189211c5432580e16802dbd71d30f0b5e124d43e4aab3ce326");
+}
+
+#[test]
+fn test_lake_keyak_regression_vectors() {
+    let mut keyak = LakeKeyak::new(&TEST_KEY, b"");
+    assert_eq!(hex::encode(keyak.encrypt(b"", b"")), "d815a2326c30a8d37e3ca7ae69f9d023");
+    let plaintext: Vec<u8> = (0..100u8).collect();
+    assert_eq!(hex::encode(keyak.encrypt(b"associated data", &plaintext)), "357126b8638bb77f5a59a9626dded2b00f864fa0669d24a5946453eca4bff0e16cc64c0639442714b217fc2395fcd7286cc5ea4c99d5088b941c214f4f9a34849516211be404bc980febd8c363d4f0e8da74250fae2185497363240f549347d5f2a6dcb41cb0ff03b55656db5c597018596015db");
+}
@@ -0,0 +1,265 @@
+use alloc::{vec, vec::Vec};
+use crate::ct::{constant_time_eq, zeroize};
+use crate::cshake::cshake_sponge;
+use crate::encoding::{left_encode, right_encode};
+use crate::{Keccak, XofReader};
+
+/// bytepad(encode_string(K), rate), built in a buffer of its final size so the
+/// key is never left behind in a reallocated copy. The caller wipes it.
+fn keypad(key: &[u8], rate: usize) -> Vec<u8> {
+    let rate_encoding = left_encode(rate as u64);
+    let length_encoding = left_encode(key.len() as u64 * 8);
+    let padded_len = (rate_encoding.len() + length_encoding.len() + key.len()).div_ceil(rate) * rate;
+    let mut out = Vec::with_capacity(padded_len);
+    out.extend_from_slice(&rate_encoding);
+    out.extend_from_slice(&length_encoding);
+    out.extend_from_slice(key);
+    out.resize(padded_len, 0);
+    out
+}
+
+macro_rules! kmac {
+    ($(#[$doc:meta])* $name:ident, $rate:expr) => {
+        $(#[$doc])*
+        ///
+        /// The key is absorbed in `new`, so a keyed instance can be cloned to
+        /// authenticate many messages without re-processing the key.
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Keccak,
+        }
+
+        impl $name {
+            pub fn new(key: &[u8], customization: &[u8]) -> Self {
+                let mut sponge = cshake_sponge($rate, b"KMAC", customization);
+                let mut padded_key = keypad(key, $rate);
+                sponge.update(&padded_key);
+                zeroize(&mut padded_key);
+                $name { sponge }
+            }
+
+            pub fn update(&mut self, input: &[u8]) {
+                self.sponge.update(input);
+            }
+
+            /// Fixed-length KMAC: the requested length is bound into the tag
+            pub fn finalize(mut self, output_len: usize) -> Vec<u8> {
+                self.sponge.update(&right_encode(output_len as u64 * 8));
+                self.sponge.finalize(output_len)
+            }
+
+            /// KMACXOF: output length is left open (encoded as 0) and read on demand
+            pub fn finalize_xof(mut self) -> XofReader {
+                self.sponge.update(&right_encode(0));
+                self.sponge.finalize_xof()
+            }
+
+            /// Recomputes the tag for `tag.len()` bytes and compares it in constant time
+            pub fn verify(self, tag: &[u8]) -> bool {
+                if tag.is_empty() {
+                    return false;
+                }
+                let mut expected = self.finalize(tag.len());
+                let valid = constant_time_eq(&expected, tag);
+                zeroize(&mut expected);
+                valid
+            }
+        }
+    };
+}
+
+kmac!(
+    /// KMAC128 (SP 800-185 section 4) keyed with K and customization string S
+    Kmac128, 168
+);
+kmac!(
+    /// KMAC256 (SP 800-185 section 4) keyed with K and customization string S
+    Kmac256, 136
+);
+
+pub fn kmac128(key: &[u8], data: &[u8], output_len: usize, customization: &[u8]) -> Vec<u8> {
+    let mut mac = Kmac128::new(key, customization);
+    mac.update(data);
+    mac.finalize(output_len)
+}
+
+pub fn kmac256(key: &[u8], data: &[u8], output_len: usize, customization: &[u8]) -> Vec<u8> {
+    let mut mac = Kmac256::new(key, customization);
+    mac.update(data);
+    mac.finalize(output_len)
+}
+
+pub fn kmac_xof128(key: &[u8], data: &[u8], output_len: usize, customization: &[u8]) -> Vec<u8> {
+    let mut mac = Kmac128::new(key, customization);
+    mac.update(data);
+    let mut output = vec![0u8; output_len];
+    mac.finalize_xof().read(&mut output);
+    output
+}
+
+pub fn kmac_xof256(key: &[u8], data: &[u8], output_len: usize, customization: &[u8]) -> Vec<u8> {
+    let mut mac = Kmac256::new(key, customization);
+    mac.update(data);
+    let mut output = vec![0u8; output_len];
+    mac.finalize_xof().read(&mut output);
+    output
+}
+
+// KMAC samples from the NIST SP 800-185 example values
+
+#[cfg(test)]
+fn sample_key() -> Vec<u8> {
+    (0x40..=0x5F).collect()
+}
+
+#[cfg(test)]
+fn sample_data_200() -> Vec<u8> {
+    (0..200).map(|i| i as u8).collect()
+}
+
+#[test]
+fn test_kmac128_sample_1() {
+    let tag = kmac128(&sample_key(), &[0x00, 0x01, 0x02, 0x03], 32, b"");
+    assert_eq!(
+        hex::encode(tag),
+        "e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e"
+    );
+}
+
+#[test]
+fn test_kmac128_sample_2() {
+    let tag = kmac128(&sample_key(), &[0x00, 0x01, 0x02, 0x03], 32, b"My Tagged Application");
+    assert_eq!(
+        hex::encode(tag),
+        "3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5"
+    );
+}
+
+#[test]
+fn test_kmac128_sample_3() {
+    let tag = kmac128(&sample_key(), &sample_data_200(), 32, b"My Tagged Application");
+    assert_eq!(
+        hex::encode(tag),
+        "1f5b4e6cca02209e0dcb5ca635b89a15e271ecc760071dfd805faa38f9729230"
+    );
+}
+
+#[test]
+fn test_kmac256_sample_4() {
+    let tag = kmac256(&sample_key(), &[0x00, 0x01, 0x02, 0x03], 64, b"My Tagged Application");
+    assert_eq!(
+        hex::encode(tag),
+        "20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d273a68d2f7f69d4cc3de9d104a351689f27cf6f5951f0103f33f4f24871024d9c27773a8dd"
+    );
+}
+
+#[test]
+fn test_kmac256_sample_5() {
+    let tag = kmac256(&sample_key(), &sample_data_200(), 64, b"");
+    assert_eq!(
+        hex::encode(tag),
+        "75358cf39e41494e949707927cee0af20a3ff553904c86b08f21cc414bcfd691589d27cf5e15369cbbff8b9a4c2eb17800855d0235ff635da82533ec6b759b69"
+    );
+}
+
+#[test]
+fn test_kmac256_sample_6() {
+    let tag = kmac256(&sample_key(), &sample_data_200(), 64, b"My Tagged Application");
+    assert_eq!(
+        hex::encode(tag),
+        "b58618f71f92e1d56c1b8c55ddd7cd188b97b4ca4d99831eb2699a837da2e4d970fbacfde50033aea585f1a2708510c32d07880801bd182898fe476876fc8965"
+    );
+}
+
+#[test]
+fn test_kmac_xof128_samples() {
+    let tag = kmac_xof128(&sample_key(), &[0x00, 0x01, 0x02, 0x03], 32, b"");
+    assert_eq!(
+        hex::encode(tag),
+        "cd83740bbd92ccc8cf032b1481a0f4460e7ca9dd12b08a0c4031178bacd6ec35"
+    );
+    let tag = kmac_xof128(&sample_key(), &[0x00, 0x01, 0x02, 0x03], 32, b"My Tagged Application");
+    assert_eq!(
+        hex::encode(tag),
+        "31a44527b4ed9f5c6101d11de6d26f0620aa5c341def41299657fe9df1a3b16c"
+    );
+    let tag = kmac_xof128(&sample_key(), &sample_data_200(), 32, b"My Tagged Application");
+    assert_eq!(
+        hex::encode(tag),
+        "47026c7cd793084aa0283c253ef658490c0db61438b8326fe9bddf281b83ae0f"
+    );
+}
+
+#[test]
+fn test_kmac_xof256_samples() {
+    let tag = kmac_xof256(&sample_key(), &[0x00, 0x01, 0x02, 0x03], 64, b"My Tagged Application");
+    assert_eq!(
+        hex::encode(tag),
+        "1755133f1534752aad0748f2c706fb5c784512cab835cd15676b16c0c6647fa96faa7af634a0bf8ff6df39374fa00fad9a39e322a7c92065a64eb1fb0801eb2b"
+    );
+    let tag = kmac_xof256(&sample_key(), &sample_data_200(), 64, b"");
+    assert_eq!(
+        hex::encode(tag),
+        "ff7b171f1e8a2b24683eed37830ee797538ba8dc563f6da1e667391a75edc02ca633079f81ce12a25f45615ec89972031d18337331d24ceb8f8ca8e6a19fd98b"
+    );
+    let tag = kmac_xof256(&sample_key(), &sample_data_200(), 64, b"My Tagged Application");
+    assert_eq!(
+        hex::encode(tag),
+        "d5be731c954ed7732846bb59dbe3a8e30f83e77a4bff4459f2f1c2b4ecebb8ce67ba01c62e8ab8578d2d499bd1bb276768781190020a306a97de281dcc30305d"
+    );
+}
+
+#[test]
+fn test_kmac_cloned_key_reuse() {
+    let keyed = Kmac128::new(&sample_key(), b"My Tagged Application");
+
+    let mut first = keyed.clone();
+    first.update(&[0x00, 0x01, 0x02, 0x03]);
+    let mut second = keyed.clone();
+    second.update(&sample_data_200());
+
+    assert_eq!(
+        hex::encode(first.finalize(32)),
+        "3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5"
+    );
+    assert_eq!(
+        hex::encode(second.finalize(32)),
+        "1f5b4e6cca02209e0dcb5ca635b89a15e271ecc760071dfd805faa38f9729230"
+    );
+}
+
+#[test]
+fn test_kmac_verify() {
+    let keyed = Kmac256::new(&sample_key(), b"");
+    let mut mac = keyed.clone();
+    mac.update(b"message");
+    let mut tag = mac.finalize(32);
+
+    let mut mac = keyed.clone();
+    mac.update(b"message");
+    assert!(mac.verify(&tag));
+
+    tag[31] ^= 1;
+    let mut mac = keyed.clone();
+    mac.update(b"message");
+    assert!(!mac.verify(&tag));
+
+    // A truncated tag is a different KMAC output (L is bound), so it must not verify
+    let mut mac = keyed.clone();
+    mac.update(b"message");
+    assert!(!mac.verify(&tag[..16]));
+
+    assert!(!keyed.verify(&[]));
+}
+
+#[test]
+fn test_keypad_matches_bytepad() {
+    use crate::encoding::{bytepad, encode_string};
+
+    for key_len in [0, 1, 32, 163, 164, 165, 300] {
+        let key: Vec<u8> = (0..key_len).map(|i| i as u8).collect();
+        for rate in [136, 168] {
+            assert_eq!(keypad(&key, rate), bytepad(&encode_string(&key), rate), "key_len = {}", key_len);
+        }
+    }
+}
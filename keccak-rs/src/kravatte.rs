@@ -0,0 +1,543 @@
+use alloc::{vec, vec::Vec};
+use crate::ct::{constant_time_eq, zeroize};
+use crate::error::AuthenticationError;
+use crate::keccak_p1600;
+
+/// Kravatte uses Keccak-p[1600, 6] for pb, pc, pd and pe
+const ROUNDS: usize = 6;
+
+/// Farfalle works on full 1600-bit blocks, both for input and output
+const BLOCK_LEN: usize = 200;
+
+/// Tag length of Kravatte-SANE (128 bits)
+pub const KRAVATTE_TAG_LEN: usize = 16;
+
+/// roll_c: updates the five lanes of the last plane,
+/// x0 <- (x0 <<< 7) ^ x1 ^ (x1 >> 3), then shifts them so that x0 moves to the end
+fn roll_c(state: &mut [u64; 25]) {
+    let lanes = &mut state[20..25];
+    let x0 = lanes[0].rotate_left(7) ^ lanes[1] ^ (lanes[1] >> 3);
+    lanes.rotate_left(1);
+    lanes[4] = x0;
+}
+
+/// roll_e: updates the ten lanes of the last two planes,
+/// x0 <- (x0 <<< 7) ^ (x1 <<< 18) ^ (x1 & (x2 >> 1)), then shifts them so that x0 moves to the end
+fn roll_e(state: &mut [u64; 25]) {
+    let lanes = &mut state[15..25];
+    let x0 = lanes[0].rotate_left(7) ^ lanes[1].rotate_left(18) ^ (lanes[1] & (lanes[2] >> 1));
+    lanes.rotate_left(1);
+    lanes[9] = x0;
+}
+
+fn xor_block(state: &mut [u64; 25], block: &[u8; BLOCK_LEN]) {
+    for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+        *lane ^= u64::from_le_bytes(bytes.try_into().unwrap());
+    }
+}
+
+/// Kravatte: the Farfalle construction on Keccak-p[1600, 6], a keyed function
+/// taking a sequence of strings and producing output of any length.
+///
+/// The key is expanded to k = pb(K || 10*). Every 200-byte block of input is
+/// masked with the next roll_c^i(k), permuted and added into an accumulator;
+/// the output blocks are pe(roll_e^j(pd(accumulator))) masked with k'. Input
+/// strings are fed with [`update`](Self::update) and closed with
+/// [`finish_string`](Self::finish_string), and [`expand`](Self::expand) gives
+/// the output for the sequence closed so far. Compression is incremental:
+/// appending a string to a sequence only costs the new string.
+///
+/// Each string is padded with 10*, so string boundaries are unambiguous. The
+/// mask following the last block of a string becomes k' for the expansion and
+/// is skipped by the next string.
+#[derive(Clone)]
+pub struct Kravatte {
+    mask: [u64; 25],          // roll_c^i(k) for the next input block
+    output_mask: [u64; 25],   // k', set when a string is finished
+    accumulator: [u64; 25],
+    buffer: Vec<u8>,          // partial block of the current string
+    string_open: bool,
+}
+
+impl Kravatte {
+    pub fn new(key: &[u8]) -> Self {
+        assert!(key.len() < BLOCK_LEN, "Kravatte key must be shorter than 200 bytes");
+
+        let mut padded = [0u8; BLOCK_LEN];
+        padded[..key.len()].copy_from_slice(key);
+        padded[key.len()] = 0x01;
+        let mut mask = [0u64; 25];
+        xor_block(&mut mask, &padded);
+        zeroize(&mut padded);
+        keccak_p1600(&mut mask, ROUNDS);
+
+        Kravatte {
+            mask,
+            output_mask: mask,
+            accumulator: [0; 25],
+            buffer: Vec::with_capacity(BLOCK_LEN),
+            string_open: false,
+        }
+    }
+
+    /// Appends `input` to the current input string
+    pub fn update(&mut self, mut input: &[u8]) {
+        self.string_open = true;
+        while !input.is_empty() {
+            let take = core::cmp::min(BLOCK_LEN - self.buffer.len(), input.len());
+            self.buffer.extend_from_slice(&input[..take]);
+            input = &input[take..];
+
+            // A full block is never the last one: padding always adds at least one bit
+            if self.buffer.len() == BLOCK_LEN {
+                let block: [u8; BLOCK_LEN] = self.buffer[..].try_into().unwrap();
+                self.compress(&block);
+                self.buffer.clear();
+            }
+        }
+    }
+
+    /// Closes the current input string (which may be empty)
+    pub fn finish_string(&mut self) {
+        self.finish_string_delimited(0x01);
+    }
+
+    /// Closes the current string after up to 7 extra bits; `delimiter` holds those
+    /// bits followed by the first padding bit, as for [`crate::Keccak`]
+    pub(crate) fn finish_string_delimited(&mut self, delimiter: u8) {
+        let mut block = [0u8; BLOCK_LEN];
+        block[..self.buffer.len()].copy_from_slice(&self.buffer);
+        block[self.buffer.len()] = delimiter;
+        self.compress(&block);
+        self.buffer.clear();
+
+        self.output_mask = self.mask;
+        roll_c(&mut self.mask);
+        self.string_open = false;
+    }
+
+    /// Fills `output` with the output of the sequence closed so far, starting
+    /// `offset` bytes into the output stream
+    pub fn expand(&self, offset: usize, output: &mut [u8]) {
+        assert!(!self.string_open, "Kravatte input string must be finished before expanding");
+
+        let mut y = self.accumulator;
+        keccak_p1600(&mut y, ROUNDS);
+        for _ in 0..offset / BLOCK_LEN {
+            roll_e(&mut y);
+        }
+
+        let mut skip = offset % BLOCK_LEN;
+        let mut written = 0;
+        while written < output.len() {
+            let mut z = y;
+            keccak_p1600(&mut z, ROUNDS);
+            let mut block = [0u8; BLOCK_LEN];
+            for (i, (lane, mask)) in z.iter().zip(self.output_mask.iter()).enumerate() {
+                block[8 * i..8 * i + 8].copy_from_slice(&(lane ^ mask).to_le_bytes());
+            }
+
+            let take = core::cmp::min(BLOCK_LEN - skip, output.len() - written);
+            output[written..written + take].copy_from_slice(&block[skip..skip + take]);
+            written += take;
+            skip = 0;
+            roll_e(&mut y);
+        }
+    }
+
+    fn compress(&mut self, block: &[u8; BLOCK_LEN]) {
+        let mut state = self.mask;
+        xor_block(&mut state, block);
+        keccak_p1600(&mut state, ROUNDS);
+        for (acc, lane) in self.accumulator.iter_mut().zip(state.iter()) {
+            *acc ^= lane;
+        }
+        roll_c(&mut self.mask);
+    }
+}
+
+impl Drop for Kravatte {
+    fn drop(&mut self) {
+        zeroize(&mut self.mask);
+        zeroize(&mut self.output_mask);
+        zeroize(&mut self.accumulator);
+        // Also wipe earlier blocks left in the spare capacity of the buffer
+        let capacity = self.buffer.capacity();
+        self.buffer.resize(capacity, 0);
+        zeroize(&mut self.buffer);
+    }
+}
+
+/// Kravatte-SANE: session authenticated encryption on Kravatte (Farfalle-SANE).
+///
+/// The session history starts with the nonce and every message appends its
+/// associated data and ciphertext, each framed with one bit telling them apart
+/// and one bit alternating between messages. Each tag is the first 16 bytes of
+/// Kravatte over the history; the keystream of the next message is taken from
+/// the same output, right after the tag.
+///
+/// Nonces must not repeat under a key. After a failed `decrypt` the session is
+/// closed and any further use panics.
+#[derive(Clone)]
+pub struct KravatteSane {
+    history: Kravatte,
+    parity: u8,   // e: alternates between consecutive messages
+    failed: bool,
+}
+
+impl KravatteSane {
+    pub fn new(key: &[u8], nonce: &[u8]) -> Self {
+        let mut history = Kravatte::new(key);
+        history.update(nonce);
+        history.finish_string();
+        KravatteSane {
+            history,
+            parity: 0,
+            failed: false,
+        }
+    }
+
+    /// Tag over the nonce alone; the sender may transmit it so that the receiver
+    /// can check that both sides start the same session
+    pub fn initial_tag(&self) -> [u8; KRAVATTE_TAG_LEN] {
+        let mut tag = [0u8; KRAVATTE_TAG_LEN];
+        self.history.expand(0, &mut tag);
+        tag
+    }
+
+    /// Encrypts `plaintext` and authenticates it with `associated_data` and the
+    /// whole session so far. Returns the ciphertext followed by the tag.
+    pub fn encrypt(&mut self, associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        assert!(!self.failed, "Kravatte-SANE session was closed by a failed decryption");
+
+        let mut output = vec![0u8; plaintext.len() + KRAVATTE_TAG_LEN];
+        let (ciphertext, tag) = output.split_at_mut(plaintext.len());
+        self.history.expand(KRAVATTE_TAG_LEN, ciphertext);
+        for (c, p) in ciphertext.iter_mut().zip(plaintext) {
+            *c ^= p;
+        }
+        self.absorb_message(associated_data, ciphertext, tag);
+        output
+    }
+
+    /// Decrypts a ciphertext produced by [`encrypt`](Self::encrypt). No plaintext
+    /// is released unless the tag verifies.
+    pub fn decrypt(&mut self, associated_data: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, AuthenticationError> {
+        assert!(!self.failed, "Kravatte-SANE session was closed by a failed decryption");
+        if ciphertext.len() < KRAVATTE_TAG_LEN {
+            self.failed = true;
+            return Err(AuthenticationError);
+        }
+        let (ciphertext, received_tag) = ciphertext.split_at(ciphertext.len() - KRAVATTE_TAG_LEN);
+
+        let mut plaintext = vec![0u8; ciphertext.len()];
+        self.history.expand(KRAVATTE_TAG_LEN, &mut plaintext);
+        for (p, c) in plaintext.iter_mut().zip(ciphertext) {
+            *p ^= c;
+        }
+        let mut tag = [0u8; KRAVATTE_TAG_LEN];
+        self.absorb_message(associated_data, ciphertext, &mut tag);
+
+        if constant_time_eq(&tag, received_tag) {
+            Ok(plaintext)
+        } else {
+            zeroize(&mut plaintext);
+            self.failed = true;
+            Err(AuthenticationError)
+        }
+    }
+
+    /// Appends A || 0 || e (if A is non-empty or there is no ciphertext) and
+    /// C || 1 || e (if C is non-empty) to the history, then outputs the tag
+    fn absorb_message(&mut self, associated_data: &[u8], ciphertext: &[u8], tag: &mut [u8]) {
+        if !associated_data.is_empty() || ciphertext.is_empty() {
+            self.history.update(associated_data);
+            self.history.finish_string_delimited(0x04 | (self.parity << 1));
+        }
+        if !ciphertext.is_empty() {
+            self.history.update(ciphertext);
+            self.history.finish_string_delimited(0x05 | (self.parity << 1));
+        }
+        self.history.expand(0, tag);
+        self.parity ^= 1;
+    }
+}
+
+/// Kravatte-WBC: a tweakable wide-block cipher on Kravatte (Farfalle-WBC).
+///
+/// The message is split into L and R by [`wbc_split`] and run through the
+/// four Farfalle-WBC layers. The outer layers H are Kravatte without the
+/// tweak and only mask the first block of their target (R0 and L0); the
+/// inner layers G take the tweak W as the first string of their input:
+///
+/// R0 ^= H(L || 0), L ^= G(W, R || 1), R ^= G(W, L || 0), L0 ^= H(R || 1)
+///
+/// Every ciphertext bit depends on every plaintext bit and the ciphertext is as
+/// long as the plaintext, so there is no expansion and no authentication beyond
+/// what redundancy in the plaintext provides.
+#[derive(Clone)]
+pub struct KravatteWbc {
+    kravatte: Kravatte,
+}
+
+/// Length in bytes of the left part of an `n`-byte Kravatte-WBC input.
+///
+/// Inputs whose halves each fit one block with their frame byte are cut in
+/// the middle (L gets the extra byte). Longer inputs follow the Farfalle-WBC
+/// split: with q the number of blocks needed by L || 0 and R || 1 together and
+/// x = floor(log2(q - 1)), L || 0 fills exactly q - 2^x whole blocks, which
+/// leaves R about 2^x blocks.
+pub(crate) fn wbc_split(n: usize) -> usize {
+    if n + 2 <= 2 * BLOCK_LEN {
+        return n.div_ceil(2);
+    }
+    let q = (n + 2).div_ceil(BLOCK_LEN);
+    let x = (q - 1).ilog2();
+    (q - (1 << x)) * BLOCK_LEN - 1
+}
+
+impl KravatteWbc {
+    pub fn new(key: &[u8]) -> Self {
+        KravatteWbc {
+            kravatte: Kravatte::new(key),
+        }
+    }
+
+    pub fn encrypt(&self, tweak: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let mut output = plaintext.to_vec();
+        let (left, right) = output.split_at_mut(wbc_split(plaintext.len()));
+        self.h_layer(left, 0x02, right);
+        self.g_layer(tweak, right, 0x03, left);
+        self.g_layer(tweak, left, 0x02, right);
+        self.h_layer(right, 0x03, left);
+        output
+    }
+
+    pub fn decrypt(&self, tweak: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+        let mut output = ciphertext.to_vec();
+        let (left, right) = output.split_at_mut(wbc_split(ciphertext.len()));
+        self.h_layer(right, 0x03, left);
+        self.g_layer(tweak, left, 0x02, right);
+        self.g_layer(tweak, right, 0x03, left);
+        self.h_layer(left, 0x02, right);
+        output
+    }
+
+    /// H: the first block of `target` ^= F(source || bit)
+    fn h_layer(&self, source: &[u8], delimiter: u8, target: &mut [u8]) {
+        let head = core::cmp::min(target.len(), BLOCK_LEN);
+        let mut kravatte = self.kravatte.clone();
+        kravatte.update(source);
+        kravatte.finish_string_delimited(delimiter);
+        xor_expansion(&kravatte, &mut target[..head]);
+    }
+
+    /// G: `target` ^= F(W, source || bit)
+    fn g_layer(&self, tweak: &[u8], source: &[u8], delimiter: u8, target: &mut [u8]) {
+        let mut kravatte = self.kravatte.clone();
+        kravatte.update(tweak);
+        kravatte.finish_string();
+        kravatte.update(source);
+        kravatte.finish_string_delimited(delimiter);
+        xor_expansion(&kravatte, target);
+    }
+}
+
+fn xor_expansion(kravatte: &Kravatte, target: &mut [u8]) {
+    let mut keystream = vec![0u8; target.len()];
+    kravatte.expand(0, &mut keystream);
+    for (t, k) in target.iter_mut().zip(keystream.iter()) {
+        *t ^= k;
+    }
+    zeroize(&mut keystream);
+}
+
+#[cfg(test)]
+use crate::spongewrap::assert_rejects_tampering;
+
+#[test]
+fn test_kravatte_rolling_functions() {
+    let mut state: [u64; 25] = core::array::from_fn(|i| i as u64 + 1);
+    roll_c(&mut state);
+    assert_eq!(&state[..20], &core::array::from_fn::<u64, 20, _>(|i| i as u64 + 1));
+    assert_eq!(&state[20..24], &[22, 23, 24, 25]);
+    assert_eq!(state[24], (21u64 << 7) ^ 22 ^ (22 >> 3));
+
+    let mut state: [u64; 25] = core::array::from_fn(|i| i as u64 + 1);
+    roll_e(&mut state);
+    assert_eq!(&state[..15], &core::array::from_fn::<u64, 15, _>(|i| i as u64 + 1));
+    assert_eq!(&state[15..24], &[17, 18, 19, 20, 21, 22, 23, 24, 25]);
+    assert_eq!(state[24], (16u64 << 7) ^ (17u64 << 18) ^ (17 & (18 >> 1)));
+}
+
+#[cfg(test)]
+fn kravatte_output(key: &[u8], strings: &[&[u8]], len: usize) -> Vec<u8> {
+    let mut kravatte = Kravatte::new(key);
+    for string in strings {
+        kravatte.update(string);
+        kravatte.finish_string();
+    }
+    let mut output = vec![0u8; len];
+    kravatte.expand(0, &mut output);
+    output
+}
+
+#[test]
+fn test_kravatte_streaming_and_offsets() {
+    let data: Vec<u8> = (0..1000).map(|i| (i * 3) as u8).collect();
+    let expected = kravatte_output(b"key", &[&data], 700);
+
+    for split in [0, 1, 199, 200, 201, 400, 999] {
+        let mut kravatte = Kravatte::new(b"key");
+        kravatte.update(&data[..split]);
+        kravatte.update(&data[split..]);
+        kravatte.finish_string();
+
+        let mut output = vec![0u8; 700];
+        kravatte.expand(0, &mut output);
+        assert_eq!(output, expected, "split = {}", split);
+
+        let mut tail = vec![0u8; 300];
+        kravatte.expand(400, &mut tail);
+        assert_eq!(tail, &expected[400..], "split = {}", split);
+    }
+}
+
+#[test]
+fn test_kravatte_string_sequences() {
+    // String boundaries and empty strings are part of the input
+    let outputs = [
+        kravatte_output(b"key", &[b"abc"], 32),
+        kravatte_output(b"key", &[b"ab", b"c"], 32),
+        kravatte_output(b"key", &[b"abc", b""], 32),
+        kravatte_output(b"key", &[b"", b"abc"], 32),
+        kravatte_output(b"key", &[b""], 32),
+        kravatte_output(b"key", &[], 32),
+        kravatte_output(b"kez", &[b"abc"], 32),
+    ];
+    for i in 0..outputs.len() {
+        for j in i + 1..outputs.len() {
+            assert_ne!(outputs[i], outputs[j], "sequences {} and {}", i, j);
+        }
+    }
+
+    // Incremental: extending a sequence gives the same output as processing it anew
+    let mut kravatte = Kravatte::new(b"key");
+    kravatte.update(b"ab");
+    kravatte.finish_string();
+    let mut first = [0u8; 32];
+    kravatte.expand(0, &mut first);
+    assert_eq!(first.to_vec(), kravatte_output(b"key", &[b"ab"], 32));
+    kravatte.update(b"c");
+    kravatte.finish_string();
+    let mut second = [0u8; 32];
+    kravatte.expand(0, &mut second);
+    assert_eq!(second.to_vec(), outputs[1]);
+}
+
+#[test]
+#[should_panic]
+fn test_kravatte_expand_requires_finished_string() {
+    let mut kravatte = Kravatte::new(b"key");
+    kravatte.update(b"open");
+    kravatte.expand(0, &mut [0u8; 8]);
+}
+
+#[test]
+fn test_kravatte_sane_round_trip_and_session() {
+    let mut sender = KravatteSane::new(b"SANE key", b"nonce");
+    let mut receiver = KravatteSane::new(b"SANE key", b"nonce");
+    assert_eq!(sender.initial_tag(), receiver.initial_tag());
+    assert_ne!(sender.initial_tag(), KravatteSane::new(b"SANE key", b"nonce2").initial_tag());
+
+    for (ad_len, len) in [(0, 0), (5, 0), (0, 5), (10, 300), (250, 1)] {
+        let ad = vec![0xA5u8; ad_len];
+        let plaintext: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let ciphertext = sender.encrypt(&ad, &plaintext);
+        assert_eq!(ciphertext.len(), len + KRAVATTE_TAG_LEN);
+        assert_eq!(receiver.decrypt(&ad, &ciphertext), Ok(plaintext));
+    }
+}
+
+#[test]
+fn test_kravatte_sane_tamper_detection() {
+    let ciphertext = KravatteSane::new(b"SANE key", b"nonce").encrypt(b"header", b"payload");
+    assert_rejects_tampering(b"SANE key", b"nonce", b"header", &ciphertext, |key, nonce, ad, ct| {
+        KravatteSane::new(key, nonce).decrypt(ad, ct)
+    });
+    assert!(KravatteSane::new(b"SANE key", b"nonc").decrypt(b"header", &ciphertext).is_err());
+
+    // Moving bytes between associated data and message is detected
+    let swapped = KravatteSane::new(b"SANE key", b"nonce").encrypt(b"", b"x");
+    assert!(KravatteSane::new(b"SANE key", b"nonce").decrypt(b"x", &swapped[1..]).is_err());
+}
+
+#[test]
+fn test_kravatte_wbc_round_trip() {
+    let wbc = KravatteWbc::new(b"WBC key");
+    for len in [0usize, 1, 2, 3, 16, 199, 200, 401, 1000] {
+        let plaintext: Vec<u8> = (0..len).map(|i| (i * 11) as u8).collect();
+        let ciphertext = wbc.encrypt(b"tweak", &plaintext);
+        assert_eq!(ciphertext.len(), len);
+        assert_eq!(wbc.decrypt(b"tweak", &ciphertext), plaintext, "len = {}", len);
+    }
+}
+
+#[test]
+fn test_kravatte_wbc_split() {
+    // Short inputs are halved, L taking the extra byte
+    assert_eq!(wbc_split(0), 0);
+    assert_eq!(wbc_split(1), 1);
+    assert_eq!(wbc_split(20), 10);
+    assert_eq!(wbc_split(398), 199);
+    // Longer ones give L || 0 a whole number of blocks
+    assert_eq!(wbc_split(399), 199);
+    assert_eq!(wbc_split(600), 399);
+    assert_eq!(wbc_split(1000), 399);
+    assert_eq!(wbc_split(1798), 199);
+    assert_eq!(wbc_split(1799), 399);
+    for n in 399..5000 {
+        let left = wbc_split(n);
+        assert_eq!((left + 1) % BLOCK_LEN, 0, "n = {}", n);
+        // L never takes more blocks than R
+        assert!((left + 1) / BLOCK_LEN <= (n - left + 1).div_ceil(BLOCK_LEN), "n = {}", n);
+    }
+}
+
+#[test]
+fn test_kravatte_wbc_diffusion_and_tweak() {
+    let wbc = KravatteWbc::new(b"WBC key");
+    let plaintext = [0u8; 64];
+    let ciphertext = wbc.encrypt(b"tweak", &plaintext);
+
+    // Changing the last plaintext byte changes both halves of the ciphertext
+    let mut modified = plaintext;
+    modified[63] = 1;
+    let other = wbc.encrypt(b"tweak", &modified);
+    assert_ne!(ciphertext[..32], other[..32]);
+    assert_ne!(ciphertext[32..], other[32..]);
+
+    assert_ne!(wbc.encrypt(b"tweaK", &plaintext), ciphertext);
+    assert_ne!(wbc.decrypt(b"tweaK", &ciphertext), plaintext);
+}
+
+#[test]
+fn test_kravatte_regression_vectors() {
+    assert_eq!(hex::encode(kravatte_output(b"", &[b""], 32)), "005ebd7dbe2d2fa3db9ab1b7d774c695b71aac711d04491b88f717b5e8b36380");
+    let data: Vec<u8> = (0..=255u8).collect();
+    let output = kravatte_output(b"Kravatte key", &[&data[..], b"second"], 232);
+    assert_eq!(hex::encode(&output[200..]), "1e28592fb803794bed4e551a61dc5b72711d89696bd5539013e8be42f2584ced");
+}
+
+#[test]
+fn test_kravatte_sane_regression_vectors() {
+    let mut sane = KravatteSane::new(b"Kravatte key", b"nonce");
+    assert_eq!(hex::encode(sane.initial_tag()), "58aa9312d1cd932d7cb40c41b121efce");
+    assert_eq!(hex::encode(sane.encrypt(b"associated data", b"plaintext")), "ab3ceb723a953ae0916d9bed6b81c317837dfbdd85f6895597");
+}
+
+#[test]
+fn test_kravatte_wbc_regression_vectors() {
+    let wbc = KravatteWbc::new(b"Kravatte key");
+    assert_eq!(hex::encode(wbc.encrypt(b"tweak", b"wide block plaintext")), "49b94a0571e65f7cd0efa0cdfac69d0dd09e75ac");
+}
@@ -1,8 +1,93 @@
+//! Keccak, SHA-3 and the Keccak-based modes.
+//!
+//! The permutations, the [`Keccak`] sponge, the fixed-output digests and the
+//! `finalize_into`/[`XofReader`] paths work without an allocator (build with
+//! `default-features = false`). The `alloc` feature adds everything returning
+//! a `Vec` (XOF one-shots, SP 800-185, the AEAD modes); `std`, on by default,
+//! adds the multi-threaded ParallelHash and KangarooTwelve and the `std::io`
+//! adapters ([`hash_reader`], [`hash_file`]).
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+use params::MAX_ROUNDS;
+
 pub mod round_constants;
+#[cfg(feature = "alloc")]
+pub mod encoding;
+pub mod midstate;
 mod keccak_f1600;
+mod keccak_f;
+mod duplex;
+#[cfg(feature = "alloc")]
+mod spongewrap;
+#[cfg(feature = "alloc")]
+mod keyak;
+#[cfg(feature = "alloc")]
+mod ketje;
+#[cfg(feature = "alloc")]
+mod kravatte;
+mod error;
+mod params;
+#[cfg(feature = "alloc")]
+mod cshake;
+#[cfg(feature = "alloc")]
+mod kmac;
+#[cfg(feature = "alloc")]
+mod tuple_hash;
+#[cfg(feature = "std")]
+mod parallel_hash;
+#[cfg(feature = "std")]
+mod parallel;
+mod turboshake;
+#[cfg(feature = "std")]
+mod kangaroo_twelve;
+mod ct;
+#[cfg(feature = "digest")]
+mod digest_impls;
+#[cfg(feature = "std")]
+mod io_impls;
 
 pub use round_constants::{RoundConstantMode, RC_TABLE};
 pub use keccak_f1600::KeccakF1600;
+pub use keccak_f::{KeccakF, KeccakSponge};
+pub use duplex::Duplex;
+#[cfg(feature = "alloc")]
+pub use spongewrap::{SpongeWrap, SPONGEWRAP_TAG_LEN};
+#[cfg(feature = "alloc")]
+pub use keyak::{LakeKeyak, RiverKeyak, KEYAK_TAG_LEN};
+#[cfg(feature = "alloc")]
+pub use ketje::{KetjeJr, KetjeSr, KETJE_TAG_LEN};
+#[cfg(feature = "alloc")]
+pub use kravatte::{Kravatte, KravatteSane, KravatteWbc, KRAVATTE_TAG_LEN};
+pub use error::{AuthenticationError, SpongeParamsError, StateImportError};
+pub use midstate::{SpongeAlgorithm, KECCAK_STATE_LEN};
+pub use params::{DomainSuffix, SpongeParams};
+#[cfg(feature = "alloc")]
+pub use cshake::{cshake128, cshake256, CShake128, CShake256};
+#[cfg(feature = "alloc")]
+pub use kmac::{kmac128, kmac256, kmac_xof128, kmac_xof256, Kmac128, Kmac256};
+#[cfg(feature = "alloc")]
+pub use tuple_hash::{tuple_hash128, tuple_hash256, tuple_hash_xof128, tuple_hash_xof256, TupleHash128, TupleHash256};
+#[cfg(feature = "std")]
+pub use parallel_hash::{
+    parallel_hash128, parallel_hash256, parallel_hash_xof128, parallel_hash_xof256, ParallelHash128, ParallelHash256,
+};
+pub use turboshake::{TurboShake128, TurboShake256, TURBOSHAKE_DEFAULT_DOMAIN};
+#[cfg(feature = "alloc")]
+pub use turboshake::{turboshake128, turboshake256};
+#[cfg(feature = "std")]
+pub use kangaroo_twelve::{kt128, kt256, Kt128, Kt256};
+pub use ct::constant_time_eq;
+#[cfg(feature = "std")]
+pub use io_impls::{hash_file, hash_reader, HashAlgorithm};
+#[cfg(feature = "digest")]
+pub use digest;
 
 /// Convenience function for Keccak-f[1600] using table-based round constants
 pub fn keccak_f1600(state: &mut [u64; 25]) {
@@ -10,27 +95,50 @@ pub fn keccak_f1600(state: &mut [u64; 25]) {
     permutation.permute(state);
 }
 
+/// Convenience function for Keccak-p[1600, nr] using table-based round constants
+pub fn keccak_p1600(state: &mut [u64; 25], rounds: usize) {
+    let permutation = KeccakF1600::new(RoundConstantMode::Table);
+    permutation.permute_rounds(state, rounds);
+}
 
+#[derive(Clone)]
 pub struct Keccak {
     state: [u64; 25],
     rate: usize,     // in bytes
     offset: usize,   // current byte offset in the rate part of the state
     delimiter: u8,   // domain separation suffix combined with first padding bit
                      // SHA-3: 0x06 (bits: 01 || 1), SHAKE: 0x1F (bits: 1111 || 1), RawSHAKE: 0x07 (bits: 11 || 1)
+                     // cSHAKE: 0x04 (bits: 00 || 1), legacy Keccak: 0x01 (no suffix, just the padding bit)
+    rounds: usize,   // nr of Keccak-p[1600, nr]; 24 except for TurboSHAKE/KangarooTwelve
+    bits: usize,     // message bits already in the byte at `offset` (only after update_bits)
 }
 
 impl Keccak {
+    /// Panics on a rate outside 1..=199 bytes or a delimiter of 0 (which lacks
+    /// the first padding bit); use [`SpongeParams`] to validate parameters that
+    /// are not known at compile time.
     pub fn new(rate: usize, delimiter: u8) -> Self {
-        assert!(rate < 200, "Rate must be less than state size (1600 bits = 200 bytes)");
+        Self::with_rounds(rate, delimiter, 24)
+    }
+
+    /// Sponge on Keccak-p[1600, rounds] instead of the full 24-round Keccak-f[1600].
+    /// Also panics on a round count outside 1..=255.
+    pub fn with_rounds(rate: usize, delimiter: u8, rounds: usize) -> Self {
+        assert!(rate > 0 && rate < 200, "Rate must be 1 to 199 bytes (the state is 1600 bits = 200 bytes)");
+        assert!(delimiter != 0, "Delimiter must contain the first padding bit");
+        assert!((1..=MAX_ROUNDS).contains(&rounds), "Round count must be in 1..=255");
         Keccak {
             state: [0; 25],
             rate,
             offset: 0,
             delimiter,
+            rounds,
+            bits: 0,
         }
     }
 
     pub fn update(&mut self, input: &[u8]) {
+        assert!(self.bits == 0, "No input can follow a message ending in a partial byte");
         for &byte in input {
             // XOR byte into state
             let lane_idx = self.offset / 8;
@@ -40,74 +148,471 @@ impl Keccak {
             self.offset += 1;
 
             if self.offset == self.rate {
-                keccak_f1600(&mut self.state);
+                keccak_p1600(&mut self.state, self.rounds);
                 self.offset = 0;
             }
         }
     }
 
-    pub fn finalize(mut self, output_len: usize) -> Vec<u8> {
-        // Padding
-        let lane_idx = self.offset / 8;
-        let byte_idx = self.offset % 8;
-        self.state[lane_idx] ^= (self.delimiter as u64) << (8 * byte_idx);
+    /// Absorbs the first `bit_len` bits of `input`, for messages whose length is
+    /// not a multiple of 8. Bits are numbered as in FIPS 202: the last
+    /// `bit_len % 8` bits are the least significant bits of the last byte, and
+    /// the other bits of that byte are ignored.
+    ///
+    /// A partial byte ends the message: it is merged with the domain suffix and
+    /// padding at finalization, and no further input is accepted.
+    pub fn update_bits(&mut self, input: &[u8], bit_len: usize) {
+        assert!(input.len() == bit_len.div_ceil(8), "Input must hold exactly bit_len bits");
+        self.update(&input[..bit_len / 8]);
+
+        let partial_bits = bit_len % 8;
+        if partial_bits != 0 {
+            let byte = input[bit_len / 8] & ((1u8 << partial_bits) - 1);
+            let lane_idx = self.offset / 8;
+            let byte_idx = self.offset % 8;
+            self.state[lane_idx] ^= (byte as u64) << (8 * byte_idx);
+            self.bits = partial_bits;
+        }
+    }
+
+    /// Applies the delimiter and pad10*1 padding and runs the final absorbing permutation
+    fn pad(&mut self) {
+        // Suffix and first padding bit follow the message bits, and may spill
+        // into the next byte (or block) after a partial byte
+        let suffix = (self.delimiter as u16) << self.bits;
+        let mut position = 8 * self.offset + 15 - suffix.leading_zeros() as usize;
+        for (i, byte) in suffix.to_le_bytes().into_iter().enumerate() {
+            if byte == 0 {
+                continue;
+            }
+            let mut offset = self.offset + i;
+            if offset == self.rate {
+                keccak_p1600(&mut self.state, self.rounds);
+                offset = 0;
+                position -= 8 * self.rate;
+            }
+            self.state[offset / 8] ^= (byte as u64) << (8 * (offset % 8));
+        }
+
+        // The final padding bit needs a block of its own if the first one took the last position
+        if position == 8 * self.rate - 1 {
+            keccak_p1600(&mut self.state, self.rounds);
+        }
 
         let last_lane_idx = (self.rate - 1) / 8;
         let last_byte_idx = (self.rate - 1) % 8;
         self.state[last_lane_idx] ^= 0x80 << (8 * last_byte_idx);
 
-        keccak_f1600(&mut self.state);
-
-        // Squeeze
-        let mut output = Vec::with_capacity(output_len);
-        while output.len() < output_len {
-            let block_size = std::cmp::min(self.rate, output_len - output.len());
-            for i in 0..block_size {
-                let lane_idx = i / 8;
-                let byte_idx = i % 8;
-                let byte = (self.state[lane_idx] >> (8 * byte_idx)) as u8;
-                output.push(byte);
-            }
-            
-            if output.len() < output_len {
-                keccak_f1600(&mut self.state);
-            }
+        keccak_p1600(&mut self.state, self.rounds);
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn finalize(self, output_len: usize) -> Vec<u8> {
+        let mut output = vec![0u8; output_len];
+        self.finalize_into(&mut output);
+        output
+    }
+
+    /// Pads the message and fills `output` without allocating
+    pub fn finalize_into(self, output: &mut [u8]) {
+        self.finalize_xof().read(output);
+    }
+
+    /// Checks in constant time that the output starts with `expected`
+    /// (the output length is `expected.len()`). An empty `expected` never verifies.
+    pub fn verify(self, expected: &[u8]) -> bool {
+        if expected.is_empty() {
+            return false;
+        }
+        let mut reader = self.finalize_xof();
+        let mut block = [0u8; 200];
+        let mut diff = 0u8;
+        for chunk in expected.chunks(block.len()) {
+            let output = &mut block[..chunk.len()];
+            reader.read(output);
+            diff |= !constant_time_eq(output, chunk) as u8;
+        }
+        ct::zeroize(&mut block);
+        diff == 0
+    }
+
+    /// Like [`finalize_into`](Self::finalize_into), but leaves the hasher reset
+    /// to an empty message with the same rate, suffix and rounds, ready for reuse
+    pub fn finalize_reset(&mut self, output: &mut [u8]) {
+        let fresh = Keccak::with_rounds(self.rate, self.delimiter, self.rounds);
+        core::mem::replace(self, fresh).finalize_into(output);
+    }
+
+    /// Discards all input absorbed so far, keeping the parameters
+    pub fn reset(&mut self) {
+        self.state = [0; 25];
+        self.offset = 0;
+        self.bits = 0;
+    }
+
+    /// Like [`finalize`](Self::finalize) with the output length in bits; the
+    /// unused high bits of the last byte are zero
+    #[cfg(feature = "alloc")]
+    pub fn finalize_bits(self, bit_len: usize) -> Vec<u8> {
+        let mut output = self.finalize(bit_len.div_ceil(8));
+        if !bit_len.is_multiple_of(8) {
+            *output.last_mut().unwrap() &= (1u8 << (bit_len % 8)) - 1;
         }
-        
         output
     }
+
+    /// Pads the message and switches to the squeezing phase, returning a reader
+    /// that produces output on demand.
+    pub fn finalize_xof(mut self) -> XofReader {
+        self.pad();
+        XofReader {
+            state: self.state,
+            rate: self.rate,
+            offset: 0,
+            rounds: self.rounds,
+        }
+    }
+}
+
+/// Wipes the state on drop, since it holds key material in keyed modes (KMAC, ...)
+/// and enough to recover recent input of an unfinished hash
+impl Drop for Keccak {
+    fn drop(&mut self) {
+        ct::zeroize(&mut self.state);
+    }
+}
+
+/// Squeezing side of the sponge, handed out by [`Keccak::finalize_xof`].
+///
+/// Output can be read in pieces of any size; the permutation only runs when
+/// the current rate block has been used up, so splitting one read into
+/// several smaller ones yields exactly the same bytes.
+#[derive(Clone)]
+pub struct XofReader {
+    state: [u64; 25],
+    rate: usize,     // in bytes
+    offset: usize,   // bytes of the current block already handed out
+    rounds: usize,
+}
+
+impl XofReader {
+    pub fn read(&mut self, output: &mut [u8]) {
+        for out in output.iter_mut() {
+            if self.offset == self.rate {
+                keccak_p1600(&mut self.state, self.rounds);
+                self.offset = 0;
+            }
+
+            let lane_idx = self.offset / 8;
+            let byte_idx = self.offset % 8;
+            *out = (self.state[lane_idx] >> (8 * byte_idx)) as u8;
+            self.offset += 1;
+        }
+    }
+}
+
+impl Drop for XofReader {
+    fn drop(&mut self) {
+        ct::zeroize(&mut self.state);
+    }
+}
+
+// Fixed-output hashers (shared by SHA-3 and the legacy Keccak variants)
+
+macro_rules! fixed_hasher {
+    ($(#[$doc:meta])* $name:ident, $rate:expr, $delimiter:expr, $output_len:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Keccak,
+        }
+
+        impl $name {
+            pub fn new() -> Self {
+                $name {
+                    sponge: Keccak::new($rate, $delimiter),
+                }
+            }
+
+            pub fn update(&mut self, input: &[u8]) {
+                self.sponge.update(input);
+            }
+
+            /// See [`Keccak::update_bits`]
+            pub fn update_bits(&mut self, input: &[u8], bit_len: usize) {
+                self.sponge.update_bits(input, bit_len);
+            }
+
+            /// Serializes the midstate, see [`Keccak::export_state`]
+            pub fn export_state(&self) -> [u8; midstate::KECCAK_STATE_LEN] {
+                self.sponge.export_state()
+            }
+
+            /// Restores a midstate exported by this same algorithm
+            pub fn import_state(data: &[u8]) -> Result<Self, StateImportError> {
+                let sponge = Keccak::import_state(data)?;
+                if (sponge.rate, sponge.delimiter, sponge.rounds) != ($rate, $delimiter, 24) {
+                    return Err(StateImportError::AlgorithmMismatch);
+                }
+                Ok($name { sponge })
+            }
+
+            pub fn finalize(self) -> [u8; $output_len] {
+                let mut out = [0u8; $output_len];
+                self.sponge.finalize_into(&mut out);
+                out
+            }
+
+            /// Returns the digest and resets the hasher for the next message
+            pub fn finalize_reset(&mut self) -> [u8; $output_len] {
+                let mut out = [0u8; $output_len];
+                self.sponge.finalize_reset(&mut out);
+                out
+            }
+
+            /// Compares the digest with `expected` in constant time
+            pub fn verify(self, expected: &[u8]) -> bool {
+                expected.len() == $output_len && self.sponge.verify(expected)
+            }
+
+            pub fn reset(&mut self) {
+                self.sponge.reset();
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
 }
 
 // SHA-3 Variants
 
+fixed_hasher!(
+    /// Incremental SHA3-224 (FIPS 202)
+    Sha3_224, 144, 0x06, 28
+);
+fixed_hasher!(
+    /// Incremental SHA3-256 (FIPS 202)
+    Sha3_256, 136, 0x06, 32
+);
+fixed_hasher!(
+    /// Incremental SHA3-384 (FIPS 202)
+    Sha3_384, 104, 0x06, 48
+);
+fixed_hasher!(
+    /// Incremental SHA3-512 (FIPS 202)
+    Sha3_512, 72, 0x06, 64
+);
+
 pub fn sha3_224(data: &[u8]) -> [u8; 28] {
     let mut hasher = Keccak::new(144, 0x06);
     hasher.update(data);
-    let out = hasher.finalize(28);
-    out.try_into().unwrap()
+    let mut out = [0u8; 28];
+    hasher.finalize_into(&mut out);
+    out
 }
 
 pub fn sha3_256(data: &[u8]) -> [u8; 32] {
     let mut hasher = Keccak::new(136, 0x06);
     hasher.update(data);
-    let out = hasher.finalize(32);
-    out.try_into().unwrap()
+    let mut out = [0u8; 32];
+    hasher.finalize_into(&mut out);
+    out
 }
 
 pub fn sha3_384(data: &[u8]) -> [u8; 48] {
     let mut hasher = Keccak::new(104, 0x06);
     hasher.update(data);
-    let out = hasher.finalize(48);
-    out.try_into().unwrap()
+    let mut out = [0u8; 48];
+    hasher.finalize_into(&mut out);
+    out
 }
 
 pub fn sha3_512(data: &[u8]) -> [u8; 64] {
     let mut hasher = Keccak::new(72, 0x06);
     hasher.update(data);
-    let out = hasher.finalize(64);
-    out.try_into().unwrap()
+    let mut out = [0u8; 64];
+    hasher.finalize_into(&mut out);
+    out
 }
 
+// SHAKE Variants (extendable-output functions)
+
+macro_rules! xof_hasher {
+    ($(#[$doc:meta])* $name:ident, $rate:expr, $delimiter:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Keccak,
+        }
+
+        impl $name {
+            pub fn new() -> Self {
+                $name {
+                    sponge: Keccak::new($rate, $delimiter),
+                }
+            }
+
+            pub fn update(&mut self, input: &[u8]) {
+                self.sponge.update(input);
+            }
+
+            /// See [`Keccak::update_bits`]
+            pub fn update_bits(&mut self, input: &[u8], bit_len: usize) {
+                self.sponge.update_bits(input, bit_len);
+            }
+
+            /// Serializes the midstate, see [`Keccak::export_state`]
+            pub fn export_state(&self) -> [u8; midstate::KECCAK_STATE_LEN] {
+                self.sponge.export_state()
+            }
+
+            /// Restores a midstate exported by this same algorithm
+            pub fn import_state(data: &[u8]) -> Result<Self, StateImportError> {
+                let sponge = Keccak::import_state(data)?;
+                if (sponge.rate, sponge.delimiter, sponge.rounds) != ($rate, $delimiter, 24) {
+                    return Err(StateImportError::AlgorithmMismatch);
+                }
+                Ok($name { sponge })
+            }
+
+            #[cfg(feature = "alloc")]
+            pub fn finalize(self, output_len: usize) -> Vec<u8> {
+                self.sponge.finalize(output_len)
+            }
+
+            /// See [`Keccak::finalize_bits`]
+            #[cfg(feature = "alloc")]
+            pub fn finalize_bits(self, bit_len: usize) -> Vec<u8> {
+                self.sponge.finalize_bits(bit_len)
+            }
+
+            pub fn finalize_into(self, output: &mut [u8]) {
+                self.sponge.finalize_into(output);
+            }
+
+            /// Fills `output` and resets the hasher for the next message
+            pub fn finalize_reset(&mut self, output: &mut [u8]) {
+                self.sponge.finalize_reset(output);
+            }
+
+            /// Compares the first `expected.len()` output bytes with `expected` in constant
+            /// time; an empty `expected` never verifies
+            pub fn verify(self, expected: &[u8]) -> bool {
+                self.sponge.verify(expected)
+            }
+
+            pub fn reset(&mut self) {
+                self.sponge.reset();
+            }
+
+            pub fn finalize_xof(self) -> XofReader {
+                self.sponge.finalize_xof()
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
+}
+
+xof_hasher!(
+    /// Incremental SHAKE128 (rate 168 bytes, 128-bit security)
+    Shake128, 168, 0x1F
+);
+xof_hasher!(
+    /// Incremental SHAKE256 (rate 136 bytes, 256-bit security)
+    Shake256, 136, 0x1F
+);
+
+#[cfg(feature = "alloc")]
+pub fn shake128(data: &[u8], output_len: usize) -> Vec<u8> {
+    let mut hasher = Shake128::new();
+    hasher.update(data);
+    hasher.finalize(output_len)
+}
+
+#[cfg(feature = "alloc")]
+pub fn shake256(data: &[u8], output_len: usize) -> Vec<u8> {
+    let mut hasher = Shake256::new();
+    hasher.update(data);
+    hasher.finalize(output_len)
+}
+
+
+// RawSHAKE Variants (SHAKE(M) = RawSHAKE(M || 11), the extra 11 being the Sakura single-node marker)
+
+xof_hasher!(
+    /// Incremental RawSHAKE128 (rate 168 bytes, suffix 11)
+    RawShake128, 168, 0x07
+);
+xof_hasher!(
+    /// Incremental RawSHAKE256 (rate 136 bytes, suffix 11)
+    RawShake256, 136, 0x07
+);
+
+#[cfg(feature = "alloc")]
+pub fn rawshake128(data: &[u8], output_len: usize) -> Vec<u8> {
+    let mut hasher = RawShake128::new();
+    hasher.update(data);
+    hasher.finalize(output_len)
+}
+
+#[cfg(feature = "alloc")]
+pub fn rawshake256(data: &[u8], output_len: usize) -> Vec<u8> {
+    let mut hasher = RawShake256::new();
+    hasher.update(data);
+    hasher.finalize(output_len)
+}
+
+// Legacy Keccak Variants (original submission padding, as used by Ethereum)
+
+fixed_hasher!(
+    /// Incremental Keccak-224 with the pre-FIPS 0x01 padding
+    Keccak224, 144, 0x01, 28
+);
+fixed_hasher!(
+    /// Incremental Keccak-256 with the pre-FIPS 0x01 padding
+    Keccak256, 136, 0x01, 32
+);
+fixed_hasher!(
+    /// Incremental Keccak-384 with the pre-FIPS 0x01 padding
+    Keccak384, 104, 0x01, 48
+);
+fixed_hasher!(
+    /// Incremental Keccak-512 with the pre-FIPS 0x01 padding
+    Keccak512, 72, 0x01, 64
+);
+
+pub fn keccak224(data: &[u8]) -> [u8; 28] {
+    let mut hasher = Keccak224::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+pub fn keccak384(data: &[u8]) -> [u8; 48] {
+    let mut hasher = Keccak384::new();
+    hasher.update(data);
+    hasher.finalize()
+}
+
+pub fn keccak512(data: &[u8]) -> [u8; 64] {
+    let mut hasher = Keccak512::new();
+    hasher.update(data);
+    hasher.finalize()
+}
 
 #[test]
 fn test_lfsr_round_constants() {
@@ -174,6 +679,71 @@ fn test_individual_steps() {
     assert_eq!(state, state2, "Manual steps should equal round() call");
 }
 
+#[test]
+fn test_keccak_p1600_24_rounds_is_keccak_f1600() {
+    let mut state_f = [0u64; 25];
+    let mut state_p = [0u64; 25];
+    keccak_f1600(&mut state_f);
+    keccak_p1600(&mut state_p, 24);
+
+    // KeccakF-1600-IntermediateValues.txt, permutation of the all-zero state
+    assert_eq!(state_f[0], 0xf1258f7940e1dde7);
+    assert_eq!(state_f[1], 0x84d5ccf933c0478a);
+    assert_eq!(state_f[24], 0xeaf1ff7b5ceca249);
+    assert_eq!(state_f, state_p);
+}
+
+#[test]
+fn test_keccak_p1600_runs_last_rounds() {
+    // A single round is the last round of Keccak-f, so only iota with RC[23] touches the zero state
+    let mut state = [0u64; 25];
+    keccak_p1600(&mut state, 1);
+    assert_eq!(state[0], 0x8000000080008008);
+    assert!(state[1..].iter().all(|&lane| lane == 0));
+
+    let mut state = [0u64; 25];
+    keccak_p1600(&mut state, 12);
+    assert_eq!(state[0], 0x8e5e5438b9a78617);
+    assert_eq!(state[1], 0xd9cd6a50f259d01e);
+    assert_eq!(state[24], 0xcffd0d76222ca01c);
+
+    let mut state = [1u64; 25];
+    keccak_p1600(&mut state, 0);
+    assert_eq!(state, [1u64; 25]);
+}
+
+#[test]
+fn test_keccak_p1600_more_than_24_rounds() {
+    // nr = 26 starts at i_r = -2, whose round constant is rc index -2 mod 255 = 253
+    let perm = KeccakF1600::new(RoundConstantMode::Table);
+    let mut expected = [0u64; 25];
+    perm.round(&mut expected, 253);
+    perm.round(&mut expected, 254);
+    perm.permute(&mut expected);
+
+    let mut state = [0u64; 25];
+    perm.permute_rounds(&mut state, 26);
+    assert_eq!(state, expected);
+}
+
+#[test]
+fn test_keccak_p1600_table_vs_lfsr() {
+    let table_perm = KeccakF1600::new(RoundConstantMode::Table);
+    let lfsr_perm = KeccakF1600::new(RoundConstantMode::Lfsr);
+
+    for rounds in [1, 6, 12, 24, 30] {
+        let mut state_table = [0u64; 25];
+        for (i, lane) in state_table.iter_mut().enumerate() {
+            *lane = i as u64 * 0x0123456789abcdef;
+        }
+        let mut state_lfsr = state_table;
+
+        table_perm.permute_rounds(&mut state_table, rounds);
+        lfsr_perm.permute_rounds(&mut state_lfsr, rounds);
+        assert_eq!(state_table, state_lfsr, "nr = {}", rounds);
+    }
+}
+
 #[test]
 fn test_sha3_256_empty() {
     let hash = sha3_256(b"");
@@ -380,4 +950,482 @@ fn test_sha3_512_million_a() {
         hex::encode(hash),
         "3c3a876da14034ab60627c077bb98f7e120a2a5370212dffb3385a18d4f38859ed311d0a9d5141ce9cc5c66ee689b266a8aa18ace8282a0e0db596c90b0a7b87"
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_sha3_256_incremental() {
+    let data = vec![b'a'; 1_000_000];
+    let mut hasher = Sha3_256::new();
+    for chunk in data.chunks(1000) {
+        hasher.update(chunk);
+    }
+    assert_eq!(
+        hex::encode(hasher.finalize_reset()),
+        "5c8875ae474a3634ba4fd55ec85bffd661f32aca75c6d699d0cdcb6c115891c1"
+    );
+    hasher.update(b"abc");
+    assert_eq!(hasher.finalize(), sha3_256(b"abc"));
+}
+
+#[test]
+fn test_sha3_fixed_hashers_match_one_shots() {
+    let data = [0x5Au8; 300];
+    let mut sha3_224_hasher = Sha3_224::new();
+    let mut sha3_384_hasher = Sha3_384::new();
+    let mut sha3_512_hasher = Sha3_512::new();
+    sha3_224_hasher.update(&data);
+    sha3_384_hasher.update(&data);
+    sha3_512_hasher.update(&data);
+    assert_eq!(sha3_224_hasher.finalize(), sha3_224(&data));
+    assert_eq!(sha3_384_hasher.finalize(), sha3_384(&data));
+    assert_eq!(sha3_512_hasher.finalize(), sha3_512(&data));
+}
+// SHAKE test vectors from the NIST FIPS 202 examples
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_shake128_empty() {
+    let out = shake128(b"", 32);
+    assert_eq!(
+        hex::encode(out),
+        "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_shake256_empty() {
+    let out = shake256(b"", 64);
+    assert_eq!(
+        hex::encode(out),
+        "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762fd75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_shake128_abc() {
+    let out = shake128(b"abc", 32);
+    assert_eq!(
+        hex::encode(out),
+        "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc8"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_shake256_abc() {
+    let out = shake256(b"abc", 64);
+    assert_eq!(
+        hex::encode(out),
+        "483366601360a8771c6863080cc4114d8db44530f8f1e1ee4f94ea37e78b5739d5a15bef186a5386c75744c0527e1faa9f8726e462a12a4feb06bd8801e751e4"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_shake128_1600_bits_long_output() {
+    // 200 bytes of 0xA3, 4096 bits of output (spans several squeeze blocks)
+    let out = shake128(&[0xA3; 200], 512);
+    assert_eq!(
+        hex::encode(&out[480..]),
+        "44c9fb359fd56ac0a9a75a743cff6862f17d7259ab075216c0699511643b6439"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_shake256_1600_bits_long_output() {
+    let out = shake256(&[0xA3; 200], 512);
+    assert_eq!(
+        hex::encode(&out[480..]),
+        "6a1a9d7846436e4dca5728b6f760eef0ca92bf0be5615e96959d767197a0beeb"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_shake128_incremental() {
+    let data = [0xA3u8; 200];
+    let mut hasher = Shake128::new();
+    for chunk in data.chunks(7) {
+        hasher.update(chunk);
+    }
+    assert_eq!(hasher.finalize(512), shake128(&data, 512));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_xof_reader_split_reads() {
+    let mut expected = vec![0u8; 1000];
+    let mut hasher = Shake256::new();
+    hasher.update(b"abc");
+    hasher.finalize_xof().read(&mut expected);
+
+    let mut hasher = Shake256::new();
+    hasher.update(b"abc");
+    let mut reader = hasher.finalize_xof();
+    let mut output = Vec::new();
+    // Uneven chunk sizes that straddle the 136-byte rate boundary
+    for size in [0, 1, 135, 1, 136, 137, 7, 300, 83, 200].iter().copied() {
+        let mut chunk = vec![0u8; size];
+        reader.read(&mut chunk);
+        output.extend_from_slice(&chunk);
+    }
+
+    assert_eq!(output, expected);
+    assert_eq!(output, shake256(b"abc", 1000));
+}
+
+#[test]
+fn test_xof_reader_byte_at_a_time() {
+    let mut hasher = Shake128::new();
+    hasher.update(&[0xA3; 200]);
+    let mut reader = hasher.finalize_xof();
+    let mut output = [0u8; 512];
+    for byte in output.iter_mut() {
+        reader.read(core::slice::from_mut(byte));
+    }
+    assert_eq!(
+        hex::encode(&output[480..]),
+        "44c9fb359fd56ac0a9a75a743cff6862f17d7259ab075216c0699511643b6439"
+    );
+}
+
+// Legacy Keccak test vectors (Ethereum's keccak256 and friends)
+
+#[test]
+fn test_keccak224_empty() {
+    assert_eq!(
+        hex::encode(keccak224(b"")),
+        "f71837502ba8e10837bdd8d365adb85591895602fc552b48b7390abd"
+    );
+}
+
+#[test]
+fn test_keccak256_empty() {
+    assert_eq!(
+        hex::encode(keccak256(b"")),
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    );
+}
+
+#[test]
+fn test_keccak256_abc() {
+    assert_eq!(
+        hex::encode(keccak256(b"abc")),
+        "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+    );
+}
+
+#[test]
+fn test_keccak256_quick_brown_fox() {
+    assert_eq!(
+        hex::encode(keccak256(b"The quick brown fox jumps over the lazy dog")),
+        "4d741b6f1eb29cb2a9b9911c82f56fa8d73b04959d3d9d222895df6c0b28aa15"
+    );
+}
+
+#[test]
+fn test_keccak256_incremental() {
+    let data = vec![b'a'; 1_000_000];
+    let mut hasher = Keccak256::new();
+    for chunk in data.chunks(1000) {
+        hasher.update(chunk);
+    }
+    assert_eq!(
+        hex::encode(hasher.finalize()),
+        "fadae6b49f129bbb812be8407b7b2894f34aecf6dbd1f9b0f0c7e9853098fc96"
+    );
+}
+
+#[test]
+fn test_keccak384_empty() {
+    assert_eq!(
+        hex::encode(keccak384(b"")),
+        "2c23146a63a29acf99e73b88f8c24eaa7dc60aa771780ccc006afbfa8fe2479b2dd2b21362337441ac12b515911957ff"
+    );
+}
+
+#[test]
+fn test_keccak512_empty() {
+    assert_eq!(
+        hex::encode(keccak512(b"")),
+        "0eab42de4c3ceb9235fc91acffe746b29c29a8c366b7c60e4e67c466f36a4304c00fa9caf9d87976ba469bcbe06713b435f091ef2769fb160cdab33d3670680e"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_shake_equals_rawshake_with_11_appended() {
+    // FIPS 202: SHAKE(M) = RawSHAKE(M || 11)
+    for len in [0usize, 1, 135, 136, 167, 168, 500] {
+        let mut data: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+        data.push(0b11);
+        let bit_len = 8 * len + 2;
+
+        let mut raw128 = RawShake128::new();
+        raw128.update_bits(&data, bit_len);
+        assert_eq!(raw128.finalize(300), shake128(&data[..len], 300));
+
+        let mut raw256 = RawShake256::new();
+        raw256.update_bits(&data, bit_len);
+        assert_eq!(raw256.finalize(300), shake256(&data[..len], 300));
+    }
+}
+
+// Bit-oriented example values from the NIST cryptographic standards examples
+// (SHA3-224_Msg5, SHA3-256_Msg5, SHA3-224_Msg30, SHA3-256_Msg30). Msg5 is
+// 11001 and Msg30 is 110010100001101011011110100110, in FIPS 202 bit order.
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_sha3_bit_oriented_5_bits() {
+    let mut hasher = Keccak::new(144, 0x06);
+    hasher.update_bits(&[0x13], 5);
+    assert_eq!(
+        hex::encode(hasher.finalize(28)),
+        "ffbad5da96bad71789330206dc6768ecaeb1b32dca6b3301489674ab"
+    );
+
+    let mut hasher = Keccak::new(136, 0x06);
+    hasher.update_bits(&[0x13], 5);
+    assert_eq!(
+        hex::encode(hasher.finalize(32)),
+        "7b0047cf5a456882363cbf0fb05322cf65f4b7059a46365e830132e3b5d957af"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_sha3_bit_oriented_30_bits() {
+    let mut hasher = Keccak::new(144, 0x06);
+    hasher.update_bits(&[0x53, 0x58, 0x7b, 0x19], 30);
+    assert_eq!(
+        hex::encode(hasher.finalize(28)),
+        "d666a514cc9dba25ac1ba69ed3930460deaac9851b5f0baab007df3b"
+    );
+
+    let mut hasher = Keccak::new(136, 0x06);
+    hasher.update_bits(&[0x53, 0x58, 0x7b, 0x19], 30);
+    assert_eq!(
+        hex::encode(hasher.finalize(32)),
+        "c8242fef409e5ae9d1f1c857ae4dc624b92b19809f62aa8c07411c54a078b1d0"
+    );
+}
+
+/// Bit-by-bit sponge following FIPS 202 literally: M || suffix || pad10*1, one bit at a time
+#[cfg(all(test, feature = "alloc"))]
+fn reference_bit_sponge(rate: usize, message: &[bool], suffix: &[bool], output_len: usize) -> Vec<u8> {
+    let mut bits = message.to_vec();
+    bits.extend_from_slice(suffix);
+    bits.push(true);
+    while !(bits.len() + 1).is_multiple_of(8 * rate) {
+        bits.push(false);
+    }
+    bits.push(true);
+
+    let mut state = [0u64; 25];
+    for block in bits.chunks(8 * rate) {
+        for (i, &bit) in block.iter().enumerate() {
+            state[i / 64] ^= (bit as u64) << (i % 64);
+        }
+        keccak_f1600(&mut state);
+    }
+    (0..output_len).map(|i| (state[i / 8] >> (8 * (i % 8))) as u8).collect()
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_update_bits_matches_bitwise_padding() {
+    // Message lengths around the block boundary exercise the suffix spilling into
+    // the next byte and the first padding bit landing on the last bit of the block
+    let rate = 168;
+    let data: Vec<u8> = (0..rate + 2).map(|i| (i * 29 + 5) as u8).collect();
+    for (delimiter, suffix) in [(0x06u8, &[false, true][..]), (0x1F, &[true; 4][..]), (0x01, &[][..])] {
+        for bit_len in 8 * (rate - 2)..=8 * (rate + 1) {
+            let message: Vec<bool> = (0..bit_len).map(|i| data[i / 8] >> (i % 8) & 1 == 1).collect();
+
+            let mut sponge = Keccak::new(rate, delimiter);
+            sponge.update_bits(&data[..bit_len.div_ceil(8)], bit_len);
+            assert_eq!(
+                sponge.finalize(32),
+                reference_bit_sponge(rate, &message, suffix, 32),
+                "delimiter = {:#04x}, bit_len = {}", delimiter, bit_len
+            );
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_update_bits_whole_bytes_is_update() {
+    let data: Vec<u8> = (0..300).map(|i| i as u8).collect();
+    let mut bits = Shake128::new();
+    bits.update_bits(&data, 8 * data.len());
+    assert_eq!(bits.finalize(64), shake128(&data, 64));
+
+    // Bits beyond bit_len in the last byte are ignored
+    let mut masked = Keccak::new(136, 0x06);
+    masked.update_bits(&[0xF3], 5);
+    let mut clean = Keccak::new(136, 0x06);
+    clean.update_bits(&[0x13], 5);
+    assert_eq!(masked.finalize(32), clean.finalize(32));
+}
+
+#[test]
+#[should_panic(expected = "Rate must be")]
+fn test_zero_rate_panics() {
+    Keccak::new(0, 0x06);
+}
+
+#[test]
+#[should_panic(expected = "first padding bit")]
+fn test_zero_delimiter_panics() {
+    Keccak::new(136, 0x00);
+}
+
+#[test]
+#[should_panic(expected = "Round count")]
+fn test_zero_rounds_panics() {
+    Keccak::with_rounds(168, 0x1F, 0);
+}
+
+#[test]
+#[should_panic(expected = "Round count")]
+fn test_too_many_rounds_panics() {
+    Keccak::with_rounds(168, 0x1F, 256);
+}
+
+#[test]
+#[should_panic]
+fn test_update_after_partial_byte_panics() {
+    let mut hasher = Shake256::new();
+    hasher.update_bits(&[0x01], 3);
+    hasher.update(b"more");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_xof_bit_output_length() {
+    let full = shake256(b"abc", 3);
+    for bit_len in 17..=24 {
+        let mut hasher = Shake256::new();
+        hasher.update(b"abc");
+        let output = hasher.finalize_bits(bit_len);
+        assert_eq!(output.len(), 3);
+        assert_eq!(&output[..2], &full[..2]);
+        assert_eq!(output[2], full[2] & (0xFFu16 >> (24 - bit_len)) as u8, "bit_len = {}", bit_len);
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_finalize_into_matches_finalize() {
+    let mut a = Keccak::new(168, 0x1F);
+    a.update(b"abc");
+    let b = a.clone();
+    let mut output = [0u8; 500];
+    a.finalize_into(&mut output);
+    assert_eq!(output.to_vec(), b.finalize(500));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_finalize_reset_reuses_hasher() {
+    let mut hasher = Keccak::new(136, 0x06);
+    let mut digest = [0u8; 32];
+    for record in [&b"first"[..], b"", b"third record"] {
+        hasher.update(record);
+        hasher.finalize_reset(&mut digest);
+        assert_eq!(digest, sha3_256(record));
+    }
+
+    // A partial byte is cleared as well
+    hasher.update_bits(&[0x13], 5);
+    hasher.reset();
+    hasher.update(b"abc");
+    hasher.finalize_reset(&mut digest);
+    assert_eq!(digest, sha3_256(b"abc"));
+
+    let mut keccak = Keccak256::new();
+    keccak.update(b"abc");
+    assert_eq!(keccak.finalize_reset(), keccak256(b"abc"));
+    assert_eq!(keccak.finalize_reset(), keccak256(b""));
+
+    let mut shake = Shake128::new();
+    shake.update(b"abc");
+    let mut output = [0u8; 64];
+    shake.finalize_reset(&mut output);
+    assert_eq!(output.to_vec(), shake128(b"abc", 64));
+    shake.finalize_into(&mut output);
+    assert_eq!(output.to_vec(), shake128(b"", 64));
+}
+
+#[test]
+fn test_keccak_state_wiped_on_drop() {
+    use std::mem::ManuallyDrop;
+
+    let mut hasher = ManuallyDrop::new(Keccak::new(136, 0x06));
+    hasher.update(&[0xA5; 300]);
+    assert!(hasher.state.iter().any(|&lane| lane != 0));
+    // SAFETY: the hasher is not used after being dropped, only its memory is
+    // inspected, and the destructor leaves valid lanes behind
+    unsafe { ManuallyDrop::drop(&mut hasher) };
+    assert_eq!(hasher.state, [0; 25]);
+
+    let mut sponge = Keccak::new(168, 0x1F);
+    sponge.update(b"secret");
+    let mut reader = ManuallyDrop::new(sponge.finalize_xof());
+    assert!(reader.state.iter().any(|&lane| lane != 0));
+    // SAFETY: as above
+    unsafe { ManuallyDrop::drop(&mut reader) };
+    assert_eq!(reader.state, [0; 25]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_constant_time_verify() {
+    let mut hasher = Keccak::new(136, 0x06);
+    hasher.update(b"abc");
+    assert!(hasher.clone().verify(&sha3_256(b"abc")));
+    assert!(!hasher.clone().verify(&sha3_256(b"abd")));
+    assert!(constant_time_eq(&sha3_256(b"abc"), &sha3_256(b"abc")));
+
+    let mut keccak = Keccak256::new();
+    keccak.update(b"abc");
+    assert!(keccak.clone().verify(&keccak256(b"abc")));
+    assert!(!keccak.clone().verify(&keccak256(b"abc")[..31]));
+
+    let expected = shake128(b"abc", 500);
+    let mut shake = Shake128::new();
+    shake.update(b"abc");
+    assert!(shake.clone().verify(&expected));
+    let mut wrong = expected.clone();
+    wrong[450] ^= 1;
+    assert!(!shake.clone().verify(&wrong));
+
+    // An empty tag must not verify
+    assert!(!shake.verify(&[]));
+    assert!(!hasher.verify(&[]));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_rawshake_differs_from_shake() {
+    assert_ne!(rawshake128(b"abc", 32), shake128(b"abc", 32));
+    assert_ne!(rawshake256(b"abc", 64), shake256(b"abc", 64));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_rawshake_xof_reader() {
+    let mut hasher = RawShake256::new();
+    hasher.update(b"abc");
+    let mut reader = hasher.finalize_xof();
+    let mut output = [0u8; 400];
+    let (head, tail) = output.split_at_mut(150);
+    reader.read(head);
+    reader.read(tail);
+    assert_eq!(output.to_vec(), rawshake256(b"abc", 400));
+}
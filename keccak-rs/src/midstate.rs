@@ -0,0 +1,268 @@
+//! Versioned serialization of the [`Keccak`] midstate, to checkpoint a
+//! running hash and resume it later, possibly in another process.
+//!
+//! Format version 1, 222 bytes, integers little-endian:
+//!
+//! | offset | size | field                                            |
+//! |--------|------|--------------------------------------------------|
+//! | 0      | 4    | magic `KCCK`                                     |
+//! | 4      | 1    | version (1)                                      |
+//! | 5      | 1    | algorithm tag ([`SpongeAlgorithm`])              |
+//! | 6      | 1    | rate in bytes                                    |
+//! | 7      | 1    | delimiter (domain suffix and first padding bit)  |
+//! | 8      | 1    | byte offset in the current block                 |
+//! | 9      | 1    | bits of a trailing partial byte                  |
+//! | 10     | 4    | rounds (1..=255)                                 |
+//! | 14     | 200  | the 25 lanes                                     |
+//! | 214    | 8    | checksum: SHAKE128 of bytes 0..214, first 8 bytes |
+//!
+//! The checksum detects corruption, not tampering: a midstate stored where an
+//! attacker can modify it must additionally be authenticated, e.g. with KMAC.
+//! The midstate of a keyed construction contains key material.
+
+use crate::error::StateImportError;
+use crate::params::MAX_ROUNDS;
+use crate::{Keccak, Shake128};
+
+/// Length of a serialized midstate
+pub const KECCAK_STATE_LEN: usize = 222;
+
+const MAGIC: &[u8; 4] = b"KCCK";
+const VERSION: u8 = 1;
+const CHECKSUM_OFFSET: usize = KECCAK_STATE_LEN - 8;
+
+/// Algorithm tag of a midstate, derived from the sponge parameters.
+///
+/// Instances sharing parameters share a tag: KMAC and TupleHash run on cSHAKE,
+/// and KangarooTwelve on TurboSHAKE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum SpongeAlgorithm {
+    Custom = 0,
+    Sha3_224 = 1,
+    Sha3_256 = 2,
+    Sha3_384 = 3,
+    Sha3_512 = 4,
+    Shake128 = 5,
+    Shake256 = 6,
+    RawShake128 = 7,
+    RawShake256 = 8,
+    Keccak224 = 9,
+    Keccak256 = 10,
+    Keccak384 = 11,
+    Keccak512 = 12,
+    CShake128 = 13,
+    CShake256 = 14,
+    TurboShake128 = 15,
+    TurboShake256 = 16,
+}
+
+impl SpongeAlgorithm {
+    fn from_params(rate: usize, delimiter: u8, rounds: usize) -> Self {
+        match (rate, delimiter, rounds) {
+            (144, 0x06, 24) => SpongeAlgorithm::Sha3_224,
+            (136, 0x06, 24) => SpongeAlgorithm::Sha3_256,
+            (104, 0x06, 24) => SpongeAlgorithm::Sha3_384,
+            (72, 0x06, 24) => SpongeAlgorithm::Sha3_512,
+            (168, 0x1F, 24) => SpongeAlgorithm::Shake128,
+            (136, 0x1F, 24) => SpongeAlgorithm::Shake256,
+            (168, 0x07, 24) => SpongeAlgorithm::RawShake128,
+            (136, 0x07, 24) => SpongeAlgorithm::RawShake256,
+            (144, 0x01, 24) => SpongeAlgorithm::Keccak224,
+            (136, 0x01, 24) => SpongeAlgorithm::Keccak256,
+            (104, 0x01, 24) => SpongeAlgorithm::Keccak384,
+            (72, 0x01, 24) => SpongeAlgorithm::Keccak512,
+            (168, 0x04, 24) => SpongeAlgorithm::CShake128,
+            (136, 0x04, 24) => SpongeAlgorithm::CShake256,
+            (168, 0x01..=0x7F, 12) => SpongeAlgorithm::TurboShake128,
+            (136, 0x01..=0x7F, 12) => SpongeAlgorithm::TurboShake256,
+            _ => SpongeAlgorithm::Custom,
+        }
+    }
+}
+
+fn checksum(data: &[u8]) -> [u8; 8] {
+    let mut hasher = Shake128::new();
+    hasher.update(data);
+    let mut out = [0u8; 8];
+    hasher.finalize_into(&mut out);
+    out
+}
+
+impl Keccak {
+    /// Algorithm tag of this sponge's parameters
+    pub fn algorithm(&self) -> SpongeAlgorithm {
+        SpongeAlgorithm::from_params(self.rate, self.delimiter, self.rounds)
+    }
+
+    /// Serializes the complete midstate (see the [format](crate::midstate))
+    pub fn export_state(&self) -> [u8; KECCAK_STATE_LEN] {
+        let mut out = [0u8; KECCAK_STATE_LEN];
+        out[0..4].copy_from_slice(MAGIC);
+        out[4] = VERSION;
+        out[5] = self.algorithm() as u8;
+        out[6] = self.rate as u8;
+        out[7] = self.delimiter;
+        out[8] = self.offset as u8;
+        out[9] = self.bits as u8;
+        out[10..14].copy_from_slice(&(self.rounds as u32).to_le_bytes());
+        for (i, lane) in self.state.iter().enumerate() {
+            out[14 + 8 * i..22 + 8 * i].copy_from_slice(&lane.to_le_bytes());
+        }
+        let sum = checksum(&out[..CHECKSUM_OFFSET]);
+        out[CHECKSUM_OFFSET..].copy_from_slice(&sum);
+        out
+    }
+
+    /// Restores a sponge from [`export_state`](Self::export_state) output.
+    /// Absorbing the rest of the message then gives the same result as if the
+    /// sponge had never been interrupted.
+    pub fn import_state(data: &[u8]) -> Result<Keccak, StateImportError> {
+        if data.len() != KECCAK_STATE_LEN {
+            return Err(StateImportError::InvalidLength(data.len()));
+        }
+        if &data[0..4] != MAGIC {
+            return Err(StateImportError::BadMagic);
+        }
+        if data[4] != VERSION {
+            return Err(StateImportError::UnsupportedVersion(data[4]));
+        }
+        if checksum(&data[..CHECKSUM_OFFSET]) != data[CHECKSUM_OFFSET..] {
+            return Err(StateImportError::ChecksumMismatch);
+        }
+
+        let rate = data[6] as usize;
+        let delimiter = data[7];
+        let offset = data[8] as usize;
+        let bits = data[9] as usize;
+        let rounds = u32::from_le_bytes(data[10..14].try_into().unwrap()) as usize;
+        let valid = rate > 0 && rate < 200 && delimiter != 0 && offset < rate && bits < 8
+            && (1..=MAX_ROUNDS).contains(&rounds)
+            && data[5] == SpongeAlgorithm::from_params(rate, delimiter, rounds) as u8;
+        if !valid {
+            return Err(StateImportError::InvalidParameters);
+        }
+
+        let mut sponge = Keccak::with_rounds(rate, delimiter, rounds);
+        for (i, lane) in sponge.state.iter_mut().enumerate() {
+            *lane = u64::from_le_bytes(data[14 + 8 * i..22 + 8 * i].try_into().unwrap());
+        }
+        sponge.offset = offset;
+        sponge.bits = bits;
+        Ok(sponge)
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_midstate_resume() {
+    use crate::sha3_256;
+
+    let data: Vec<u8> = (0..10_000).map(|i| (i % 251) as u8).collect();
+    for split in [0, 1, 135, 136, 137, 5000] {
+        let mut hasher = Keccak::new(136, 0x06);
+        hasher.update(&data[..split]);
+        let checkpoint = hasher.export_state();
+
+        let mut resumed = Keccak::import_state(&checkpoint).unwrap();
+        assert_eq!(resumed.algorithm(), SpongeAlgorithm::Sha3_256);
+        resumed.update(&data[split..]);
+        assert_eq!(resumed.finalize(32), sha3_256(&data), "split = {}", split);
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_midstate_keeps_all_parameters() {
+    use crate::turboshake128;
+
+    let mut hasher = Keccak::with_rounds(168, 0x0B, 12);
+    hasher.update(b"partial");
+    let resumed = Keccak::import_state(&hasher.export_state()).unwrap();
+    assert_eq!(resumed.algorithm(), SpongeAlgorithm::TurboShake128);
+    assert_eq!(resumed.finalize(64), turboshake128(b"partial", 0x0B, 64));
+
+    // A trailing partial byte survives the round trip
+    let mut bits = Keccak::new(136, 0x06);
+    bits.update_bits(&[0x13], 5);
+    let resumed = Keccak::import_state(&bits.export_state()).unwrap();
+    assert_eq!(resumed.finalize(32), bits.finalize(32));
+
+    let custom = Keccak::new(100, 0x05);
+    assert_eq!(custom.algorithm(), SpongeAlgorithm::Custom);
+    assert!(Keccak::import_state(&custom.export_state()).is_ok());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_midstate_fork_shared_prefix() {
+    use crate::shake256;
+
+    let mut prefix = Keccak::new(136, 0x1F);
+    prefix.update(b"shared prefix ");
+    for suffix in [&b"one"[..], b"two"] {
+        let mut fork = prefix.clone();
+        fork.update(suffix);
+        assert_eq!(fork.finalize(32), shake256(&[&b"shared prefix "[..], suffix].concat(), 32));
+    }
+}
+
+#[test]
+fn test_midstate_rejects_corruption() {
+    let mut hasher = Keccak::new(168, 0x1F);
+    hasher.update(b"abc");
+    let exported = hasher.export_state();
+
+    for i in 0..KECCAK_STATE_LEN {
+        let mut corrupted = exported;
+        corrupted[i] ^= 0x01;
+        assert!(Keccak::import_state(&corrupted).is_err(), "flipped byte {}", i);
+    }
+    assert_eq!(Keccak::import_state(&exported[1..]).err(), Some(StateImportError::InvalidLength(221)));
+    assert_eq!(Keccak::import_state(&[0u8; KECCAK_STATE_LEN]).err(), Some(StateImportError::BadMagic));
+
+    let mut future = exported;
+    future[4] = 2;
+    assert_eq!(Keccak::import_state(&future).err(), Some(StateImportError::UnsupportedVersion(2)));
+
+    // Consistent checksum, inconsistent fields
+    let mut bad_offset = exported;
+    bad_offset[8] = 200;
+    let sum = checksum(&bad_offset[..CHECKSUM_OFFSET]);
+    bad_offset[CHECKSUM_OFFSET..].copy_from_slice(&sum);
+    assert_eq!(Keccak::import_state(&bad_offset).err(), Some(StateImportError::InvalidParameters));
+
+    // Untrusted round counts must not reach the permutation
+    for rounds in [0u32, 256, u32::MAX] {
+        let mut bad_rounds = exported;
+        bad_rounds[10..14].copy_from_slice(&rounds.to_le_bytes());
+        let sum = checksum(&bad_rounds[..CHECKSUM_OFFSET]);
+        bad_rounds[CHECKSUM_OFFSET..].copy_from_slice(&sum);
+        assert_eq!(
+            Keccak::import_state(&bad_rounds).err(),
+            Some(StateImportError::InvalidParameters),
+            "rounds = {}",
+            rounds
+        );
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_midstate_wrapper_types() {
+    use crate::{keccak256, shake128, Keccak256, Shake128, Shake256};
+
+    let mut shake = Shake128::new();
+    shake.update(b"ab");
+    let exported = shake.export_state();
+    let mut resumed = Shake128::import_state(&exported).unwrap();
+    resumed.update(b"c");
+    assert_eq!(resumed.finalize(32), shake128(b"abc", 32));
+    assert_eq!(Shake256::import_state(&exported).err(), Some(StateImportError::AlgorithmMismatch));
+
+    let mut keccak = Keccak256::new();
+    keccak.update(b"ab");
+    let mut resumed = Keccak256::import_state(&keccak.export_state()).unwrap();
+    resumed.update(b"c");
+    assert_eq!(resumed.finalize(), keccak256(b"abc"));
+}
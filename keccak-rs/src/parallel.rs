@@ -0,0 +1,188 @@
+use std::thread;
+
+/// Number of worker threads to use when the caller doesn't pick one
+pub(crate) fn default_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// Hashes `data` as a sequence of `leaf_size` leaves (the last one may be shorter)
+/// and returns the concatenated chaining values in leaf order.
+///
+/// Leaves are split into contiguous groups, one per worker thread. `leaf` appends
+/// the chaining value of a single leaf to the output buffer. If a worker can't be
+/// spawned its group is hashed on the calling thread, so the result never depends
+/// on how many threads were actually available.
+pub(crate) fn hash_leaves<F>(data: &[u8], leaf_size: usize, threads: usize, leaf: F) -> Vec<u8>
+where
+    F: Fn(&[u8], &mut Vec<u8>) + Sync,
+{
+    let hash_group = |group: &[u8]| {
+        let mut cvs = Vec::new();
+        for leaf_data in group.chunks(leaf_size) {
+            leaf(leaf_data, &mut cvs);
+        }
+        cvs
+    };
+
+    let leaves = data.len().div_ceil(leaf_size);
+    let threads = threads.clamp(1, leaves.max(1));
+    if threads == 1 {
+        return hash_group(data);
+    }
+
+    let group_size = leaves.div_ceil(threads) * leaf_size;
+    let hash_group = &hash_group;
+    thread::scope(|scope| {
+        let workers: Vec<_> = data
+            .chunks(group_size)
+            .map(|group| {
+                let handle = thread::Builder::new()
+                    .spawn_scoped(scope, move || hash_group(group))
+                    .ok();
+                (group, handle)
+            })
+            .collect();
+
+        let mut cvs = Vec::new();
+        for (group, handle) in workers {
+            match handle {
+                Some(handle) => cvs.extend(handle.join().expect("leaf worker panicked")),
+                None => cvs.extend(hash_group(group)),
+            }
+        }
+        cvs
+    })
+}
+
+/// Smallest share of a batch given to one worker, so that spawning the
+/// threads stays cheap next to the hashing itself.
+const MIN_THREAD_BYTES: usize = 1 << 18;
+
+/// Largest number of leaves hashed by one `hash_leaves` call on input taken
+/// straight from the caller, which bounds the chaining values held at once.
+const MAX_SEGMENT_LEAVES: usize = 1 << 16;
+
+/// Cuts streamed input into whole leaves for [`hash_leaves`].
+///
+/// Inputs of at least a batch are hashed in place from the caller's slice and
+/// only their partial last leaf is kept. Smaller writes are collected until a
+/// batch is complete, so the buffer never exceeds [`LeafStream::batch_len`].
+#[derive(Clone)]
+pub(crate) struct LeafStream {
+    leaf_size: usize,
+    threads: usize,
+    buffer: Vec<u8>,  // pending input, always starting on a leaf boundary
+}
+
+impl LeafStream {
+    pub(crate) fn new(leaf_size: usize, threads: usize) -> Self {
+        LeafStream {
+            leaf_size,
+            threads: threads.max(1),
+            buffer: Vec::new(),
+        }
+    }
+
+    pub(crate) fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    /// Whole leaves giving every worker at least `MIN_THREAD_BYTES`
+    fn batch_len(&self) -> usize {
+        let per_thread = MIN_THREAD_BYTES.div_ceil(self.leaf_size).saturating_mul(self.leaf_size);
+        let batch = per_thread.saturating_mul(self.threads);
+        batch - batch % self.leaf_size
+    }
+
+    fn segment_len(&self) -> usize {
+        let segment = self.leaf_size.saturating_mul(MAX_SEGMENT_LEAVES).max(self.batch_len());
+        segment - segment % self.leaf_size
+    }
+
+    /// Hashes every complete batch of leaves and passes their chaining values to `sink`
+    pub(crate) fn update<F>(&mut self, mut input: &[u8], leaf: &F, mut sink: impl FnMut(&[u8]))
+    where
+        F: Fn(&[u8], &mut Vec<u8>) + Sync,
+    {
+        let batch = self.batch_len();
+        if !self.buffer.is_empty() {
+            let take = core::cmp::min(batch - self.buffer.len(), input.len());
+            self.buffer.extend_from_slice(&input[..take]);
+            input = &input[take..];
+            if self.buffer.len() < batch {
+                return;
+            }
+            sink(&hash_leaves(&self.buffer, self.leaf_size, self.threads, leaf));
+            self.buffer.clear();
+        }
+
+        if input.len() < batch {
+            self.buffer.extend_from_slice(input);
+            return;
+        }
+        let full = input.len() - input.len() % self.leaf_size;
+        for segment in input[..full].chunks(self.segment_len()) {
+            sink(&hash_leaves(segment, self.leaf_size, self.threads, leaf));
+        }
+        self.buffer.extend_from_slice(&input[full..]);
+    }
+
+    /// Hashes the pending input, whose last leaf may be partial
+    pub(crate) fn finish<F>(&mut self, leaf: &F, mut sink: impl FnMut(&[u8]))
+    where
+        F: Fn(&[u8], &mut Vec<u8>) + Sync,
+    {
+        if !self.buffer.is_empty() {
+            sink(&hash_leaves(&self.buffer, self.leaf_size, self.threads, leaf));
+            self.buffer.clear();
+        }
+    }
+}
+
+#[test]
+fn test_hash_leaves_independent_of_thread_count() {
+    let data: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+    let leaf = |leaf_data: &[u8], out: &mut Vec<u8>| {
+        out.push(leaf_data.len() as u8);
+        out.push(leaf_data.iter().fold(0u8, |acc, &b| acc.wrapping_add(b)));
+    };
+
+    let single = hash_leaves(&data, 64, 1, leaf);
+    assert_eq!(single.len(), 2 * 16);
+    for threads in [0, 2, 3, 7, 16, 100] {
+        assert_eq!(hash_leaves(&data, 64, threads, leaf), single);
+    }
+    assert!(hash_leaves(&[], 64, 4, leaf).is_empty());
+}
+
+#[test]
+fn test_leaf_stream_independent_of_write_sizes() {
+    let data: Vec<u8> = (0..3_000_000u32).map(|i| (i % 253) as u8).collect();
+    let leaf = |leaf_data: &[u8], out: &mut Vec<u8>| {
+        out.extend_from_slice(&(leaf_data.len() as u32).to_le_bytes());
+        out.push(leaf_data.iter().fold(0u8, |acc, &b| acc.wrapping_mul(31).wrapping_add(b)));
+    };
+    let expected = hash_leaves(&data, 1000, 1, leaf);
+
+    for (threads, write_len) in [(1, 3_000_000), (4, 1), (4, 999), (3, 70_001), (8, 1_500_000)] {
+        let mut stream = LeafStream::new(1000, threads);
+        let mut cvs = Vec::new();
+        for chunk in data.chunks(write_len) {
+            stream.update(chunk, &leaf, |out| cvs.extend_from_slice(out));
+            assert!(stream.buffer.len() < stream.batch_len());
+        }
+        stream.finish(&leaf, |out| cvs.extend_from_slice(out));
+        assert_eq!(cvs, expected, "threads {} write_len {}", threads, write_len);
+    }
+}
+
+#[test]
+fn test_leaf_stream_large_leaves_do_not_overflow() {
+    let mut stream = LeafStream::new(usize::MAX / 2, usize::MAX);
+    assert!(stream.batch_len() >= usize::MAX / 2);
+    let leaf = |leaf_data: &[u8], out: &mut Vec<u8>| out.push(leaf_data.len() as u8);
+    let mut cvs = Vec::new();
+    stream.update(&[1, 2, 3], &leaf, |out| cvs.extend_from_slice(out));
+    stream.finish(&leaf, |out| cvs.extend_from_slice(out));
+    assert_eq!(cvs, [3]);
+}
@@ -0,0 +1,247 @@
+use crate::cshake::cshake_sponge;
+use crate::encoding::{left_encode, right_encode};
+use crate::parallel::{default_threads, LeafStream};
+use crate::{Keccak, XofReader};
+
+macro_rules! parallel_hash {
+    ($(#[$doc:meta])* $name:ident, $rate:expr, $cv_len:expr) => {
+        $(#[$doc])*
+        ///
+        /// The input is cut into blocks of `block_size` bytes, each block is hashed
+        /// independently with cSHAKE (N = S = ""), and the chaining values are
+        /// absorbed by the outer cSHAKE. The output does not depend on the
+        /// number of threads.
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Keccak,
+            stream: LeafStream,  // input not yet hashed into blocks
+            blocks: u64,         // number of chaining values absorbed so far
+        }
+
+        impl $name {
+            pub fn new(block_size: usize, customization: &[u8]) -> Self {
+                assert!(block_size > 0, "ParallelHash block size must be positive");
+                let mut sponge = cshake_sponge($rate, b"ParallelHash", customization);
+                sponge.update(&left_encode(block_size as u64));
+                $name {
+                    sponge,
+                    stream: LeafStream::new(block_size, default_threads()),
+                    blocks: 0,
+                }
+            }
+
+            /// Sets the number of worker threads; 0 and 1 both hash on the calling thread
+            pub fn with_threads(mut self, threads: usize) -> Self {
+                self.stream.set_threads(threads);
+                self
+            }
+
+            pub fn update(&mut self, input: &[u8]) {
+                let $name { sponge, stream, blocks, .. } = self;
+                stream.update(input, &Self::leaf, |cvs| Self::absorb_cvs(sponge, blocks, cvs));
+            }
+
+            fn leaf(block: &[u8], out: &mut Vec<u8>) {
+                let mut leaf = Keccak::new($rate, 0x1F);
+                leaf.update(block);
+                let mut cv = [0u8; $cv_len];
+                leaf.finalize_xof().read(&mut cv);
+                out.extend_from_slice(&cv);
+            }
+
+            fn absorb_cvs(sponge: &mut Keccak, blocks: &mut u64, cvs: &[u8]) {
+                *blocks += (cvs.len() / $cv_len) as u64;
+                sponge.update(cvs);
+            }
+
+            fn finish(&mut self, output_bits: u64) {
+                let $name { sponge, stream, blocks, .. } = self;
+                stream.finish(&Self::leaf, |cvs| Self::absorb_cvs(sponge, blocks, cvs));
+                self.sponge.update(&right_encode(self.blocks));
+                self.sponge.update(&right_encode(output_bits));
+            }
+
+            pub fn finalize(mut self, output_len: usize) -> Vec<u8> {
+                self.finish(output_len as u64 * 8);
+                self.sponge.finalize(output_len)
+            }
+
+            /// ParallelHashXOF: output length is encoded as 0 and read on demand
+            pub fn finalize_xof(mut self) -> XofReader {
+                self.finish(0);
+                self.sponge.finalize_xof()
+            }
+        }
+    };
+}
+
+parallel_hash!(
+    /// ParallelHash128 (SP 800-185 section 6) with a configurable block size B
+    ParallelHash128, 168, 32
+);
+parallel_hash!(
+    /// ParallelHash256 (SP 800-185 section 6) with a configurable block size B
+    ParallelHash256, 136, 64
+);
+
+pub fn parallel_hash128(data: &[u8], block_size: usize, output_len: usize, customization: &[u8]) -> Vec<u8> {
+    let mut hasher = ParallelHash128::new(block_size, customization);
+    hasher.update(data);
+    hasher.finalize(output_len)
+}
+
+pub fn parallel_hash256(data: &[u8], block_size: usize, output_len: usize, customization: &[u8]) -> Vec<u8> {
+    let mut hasher = ParallelHash256::new(block_size, customization);
+    hasher.update(data);
+    hasher.finalize(output_len)
+}
+
+pub fn parallel_hash_xof128(data: &[u8], block_size: usize, output_len: usize, customization: &[u8]) -> Vec<u8> {
+    let mut hasher = ParallelHash128::new(block_size, customization);
+    hasher.update(data);
+    let mut output = vec![0u8; output_len];
+    hasher.finalize_xof().read(&mut output);
+    output
+}
+
+pub fn parallel_hash_xof256(data: &[u8], block_size: usize, output_len: usize, customization: &[u8]) -> Vec<u8> {
+    let mut hasher = ParallelHash256::new(block_size, customization);
+    hasher.update(data);
+    let mut output = vec![0u8; output_len];
+    hasher.finalize_xof().read(&mut output);
+    output
+}
+
+// ParallelHash samples from the NIST SP 800-185 example values
+
+/// 00..07 10..17 20..27, hashed with B = 8
+#[cfg(test)]
+fn sample_data_24() -> Vec<u8> {
+    [0x00u8, 0x10, 0x20].iter().flat_map(|&row| (0..8).map(move |i| row + i)).collect()
+}
+
+/// 00..0B 10..1B ... 50..5B, hashed with B = 12
+#[cfg(test)]
+fn sample_data_72() -> Vec<u8> {
+    [0x00u8, 0x10, 0x20, 0x30, 0x40, 0x50].iter().flat_map(|&row| (0..12).map(move |i| row + i)).collect()
+}
+
+#[test]
+fn test_parallel_hash128_samples() {
+    let out = parallel_hash128(&sample_data_24(), 8, 32, b"");
+    assert_eq!(
+        hex::encode(out),
+        "ba8dc1d1d979331d3f813603c67f72609ab5e44b94a0b8f9af46514454a2b4f5"
+    );
+    let out = parallel_hash128(&sample_data_24(), 8, 32, b"Parallel Data");
+    assert_eq!(
+        hex::encode(out),
+        "fc484dcb3f84dceedc353438151bee58157d6efed0445a81f165e495795b7206"
+    );
+    let out = parallel_hash128(&sample_data_72(), 12, 32, b"Parallel Data");
+    assert_eq!(
+        hex::encode(out),
+        "f7fd5312896c6685c828af7e2adb97e393e7f8d54e3c2ea4b95e5aca3796e8fc"
+    );
+}
+
+#[test]
+fn test_parallel_hash256_samples() {
+    let out = parallel_hash256(&sample_data_24(), 8, 64, b"");
+    assert_eq!(
+        hex::encode(out),
+        "bc1ef124da34495e948ead207dd9842235da432d2bbc54b4c110e64c451105531b7f2a3e0ce055c02805e7c2de1fb746af97a1dd01f43b824e31b87612410429"
+    );
+    let out = parallel_hash256(&sample_data_24(), 8, 64, b"Parallel Data");
+    assert_eq!(
+        hex::encode(out),
+        "cdf15289b54f6212b4bc270528b49526006dd9b54e2b6add1ef6900dda3963bb33a72491f236969ca8afaea29c682d47a393c065b38e29fae651a2091c833110"
+    );
+    let out = parallel_hash256(&sample_data_72(), 12, 64, b"Parallel Data");
+    assert_eq!(
+        hex::encode(out),
+        "69d0fcb764ea055dd09334bc6021cb7e4b61348dff375da262671cdec3effa8d1b4568a6cce16b1cad946ddde27f6ce2b8dee4cd1b24851ebf00eb90d43813e9"
+    );
+}
+
+#[test]
+fn test_parallel_hash_xof128_samples() {
+    let out = parallel_hash_xof128(&sample_data_24(), 8, 32, b"");
+    assert_eq!(
+        hex::encode(out),
+        "fe47d661e49ffe5b7d999922c062356750caf552985b8e8ce6667f2727c3c8d3"
+    );
+    let out = parallel_hash_xof128(&sample_data_24(), 8, 32, b"Parallel Data");
+    assert_eq!(
+        hex::encode(out),
+        "ea2a793140820f7a128b8eb70a9439f93257c6e6e79b4a540d291d6dae7098d7"
+    );
+    let out = parallel_hash_xof128(&sample_data_72(), 12, 32, b"Parallel Data");
+    assert_eq!(
+        hex::encode(out),
+        "0127ad9772ab904691987fcc4a24888f341fa0db2145e872d4efd255376602f0"
+    );
+}
+
+#[test]
+fn test_parallel_hash_xof256_samples() {
+    let out = parallel_hash_xof256(&sample_data_24(), 8, 64, b"");
+    assert_eq!(
+        hex::encode(out),
+        "c10a052722614684144d28474850b410757e3cba87651ba167a5cbddff7f466675fbf84bcae7378ac444be681d729499afca667fb879348bfdda427863c82f1c"
+    );
+    let out = parallel_hash_xof256(&sample_data_24(), 8, 64, b"Parallel Data");
+    assert_eq!(
+        hex::encode(out),
+        "538e105f1a22f44ed2f5cc1674fbd40be803d9c99bf5f8d90a2c8193f3fe6ea768e5c1a20987e2c9c65febed03887a51d35624ed12377594b5585541dc377efc"
+    );
+    let out = parallel_hash_xof256(&sample_data_72(), 12, 64, b"Parallel Data");
+    assert_eq!(
+        hex::encode(out),
+        "6b3e790b330c889a204c2fbc728d809f19367328d852f4002dc829f73afd6bcefb7fe5b607b13a801c0be5c1170bdb794e339458fdb0e62a6af3d42558970249"
+    );
+}
+
+#[test]
+fn test_parallel_hash_empty_input() {
+    // No blocks at all: only left_encode(B), right_encode(0) and right_encode(L) are absorbed
+    let out = parallel_hash128(b"", 8, 32, b"");
+    assert_eq!(
+        hex::encode(out),
+        "96427c30224408859f95e89e4fa84e1c7a1478dbf2008ac982ce61a77f37a272"
+    );
+}
+
+#[test]
+fn test_parallel_hash_thread_count_and_chunking() {
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    let expected = "9d9a095b1436339516c586467e1ded2a42997d42163c2338968631fbe39e9864";
+
+    for threads in [1, 2, 3, 8] {
+        let mut hasher = ParallelHash128::new(1000, b"big").with_threads(threads);
+        hasher.update(&data);
+        assert_eq!(hex::encode(hasher.finalize(32)), expected, "threads = {}", threads);
+
+        // Uneven updates that don't line up with block boundaries
+        let mut hasher = ParallelHash128::new(1000, b"big").with_threads(threads);
+        for chunk in data.chunks(777) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hex::encode(hasher.finalize(32)), expected, "threads = {}", threads);
+    }
+}
+
+#[test]
+fn test_parallel_hash_batched_update() {
+    // Several MiB, so update() hashes complete blocks in place and in batches
+    let data: Vec<u8> = (0..((2 << 20) + 12345)).map(|i| (i % 253) as u8).collect();
+    let mut single = ParallelHash256::new(8192, b"").with_threads(1);
+    single.update(&data);
+    let expected = single.finalize(64);
+
+    let mut hasher = ParallelHash256::new(8192, b"").with_threads(4);
+    for chunk in data.chunks(100_000) {
+        hasher.update(chunk);
+    }
+    assert_eq!(hasher.finalize(64), expected);
+}
@@ -0,0 +1,191 @@
+use crate::error::SpongeParamsError;
+use crate::Keccak;
+
+/// Size of the state of Keccak-f[1600] in bytes
+const STATE_BYTES: usize = 200;
+
+/// Largest accepted number of rounds. Round indices of Keccak-p[1600, nr] are
+/// taken mod 255, so longer schedules only repeat round constants.
+pub(crate) const MAX_ROUNDS: usize = 255;
+
+/// Domain separation suffix of a sponge, stored as the delimiter byte used by
+/// [`Keccak::new`]: the suffix bits followed by the first padding bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DomainSuffix(u8);
+
+impl DomainSuffix {
+    /// SHA3-224..512: suffix 01
+    pub const SHA3: DomainSuffix = DomainSuffix(0x06);
+    /// SHAKE128/256: suffix 1111
+    pub const SHAKE: DomainSuffix = DomainSuffix(0x1F);
+    /// RawSHAKE128/256: suffix 11
+    pub const RAW_SHAKE: DomainSuffix = DomainSuffix(0x07);
+    /// cSHAKE with a non-empty N or S: suffix 00
+    pub const CSHAKE: DomainSuffix = DomainSuffix(0x04);
+    /// Original Keccak submission (legacy Keccak-256 etc.): no suffix
+    pub const KECCAK: DomainSuffix = DomainSuffix(0x01);
+
+    /// Any other delimiter byte, e.g. a TurboSHAKE domain byte. It must contain
+    /// the padding bit, so 0x00 is rejected.
+    pub fn new(delimiter: u8) -> Result<Self, SpongeParamsError> {
+        if delimiter == 0 {
+            return Err(SpongeParamsError::InvalidSuffix(delimiter));
+        }
+        Ok(DomainSuffix(delimiter))
+    }
+
+    pub fn delimiter(self) -> u8 {
+        self.0
+    }
+}
+
+impl From<DomainSuffix> for u8 {
+    fn from(suffix: DomainSuffix) -> u8 {
+        suffix.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Size {
+    RateBytes(usize),
+    CapacityBits(usize),
+}
+
+/// Validated construction of a [`Keccak`] sponge, for parameters that come
+/// from configuration rather than from code.
+///
+/// The size can be given as a rate in bytes, a capacity in bits or a security
+/// level in bits (capacity = 2 * level); the last one set wins. Invalid values
+/// are reported by [`build`](Self::build) instead of panicking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpongeParams {
+    size: Option<Size>,
+    suffix: DomainSuffix,
+    rounds: usize,
+}
+
+impl SpongeParams {
+    /// No size yet, the SHAKE suffix and 24 rounds
+    pub fn new() -> Self {
+        SpongeParams {
+            size: None,
+            suffix: DomainSuffix::SHAKE,
+            rounds: 24,
+        }
+    }
+
+    pub fn rate(mut self, rate_bytes: usize) -> Self {
+        self.size = Some(Size::RateBytes(rate_bytes));
+        self
+    }
+
+    pub fn capacity(mut self, capacity_bits: usize) -> Self {
+        self.size = Some(Size::CapacityBits(capacity_bits));
+        self
+    }
+
+    /// Capacity of twice `bits`, the generic security level of the sponge
+    pub fn security_level(mut self, bits: usize) -> Self {
+        self.size = Some(Size::CapacityBits(bits.saturating_mul(2)));
+        self
+    }
+
+    pub fn suffix(mut self, suffix: DomainSuffix) -> Self {
+        self.suffix = suffix;
+        self
+    }
+
+    /// Number of rounds of Keccak-p[1600, nr]
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    /// Rate in bytes implied by the size given, once validated
+    pub fn rate_bytes(&self) -> Result<usize, SpongeParamsError> {
+        let rate = match self.size {
+            None => return Err(SpongeParamsError::MissingCapacity),
+            Some(Size::RateBytes(rate)) => rate,
+            Some(Size::CapacityBits(bits)) => {
+                if !bits.is_multiple_of(8) {
+                    return Err(SpongeParamsError::CapacityNotByteAligned(bits));
+                }
+                if bits == 0 || bits >= 8 * STATE_BYTES {
+                    return Err(SpongeParamsError::InvalidRate(STATE_BYTES.saturating_sub(bits / 8)));
+                }
+                STATE_BYTES - bits / 8
+            }
+        };
+        if rate == 0 || rate >= STATE_BYTES {
+            return Err(SpongeParamsError::InvalidRate(rate));
+        }
+        Ok(rate)
+    }
+
+    pub fn build(&self) -> Result<Keccak, SpongeParamsError> {
+        let rate = self.rate_bytes()?;
+        if !(1..=MAX_ROUNDS).contains(&self.rounds) {
+            return Err(SpongeParamsError::InvalidRounds(self.rounds));
+        }
+        Ok(Keccak::with_rounds(rate, self.suffix.delimiter(), self.rounds))
+    }
+}
+
+impl Default for SpongeParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_sponge_params_standard_instances() {
+    use crate::{keccak256, sha3_512, shake128, turboshake128};
+
+    let mut sponge = SpongeParams::new().security_level(512).suffix(DomainSuffix::SHA3).build().unwrap();
+    sponge.update(b"abc");
+    assert_eq!(sponge.finalize(64), sha3_512(b"abc"));
+
+    let mut sponge = SpongeParams::new().capacity(256).build().unwrap();
+    sponge.update(b"abc");
+    assert_eq!(sponge.finalize(100), shake128(b"abc", 100));
+
+    let mut sponge = SpongeParams::new().rate(136).suffix(DomainSuffix::KECCAK).build().unwrap();
+    sponge.update(b"abc");
+    assert_eq!(sponge.finalize(32), keccak256(b"abc"));
+
+    let domain = DomainSuffix::new(0x0B).unwrap();
+    let mut sponge = SpongeParams::new().rate(168).suffix(domain).rounds(12).build().unwrap();
+    sponge.update(b"abc");
+    assert_eq!(sponge.finalize(32), turboshake128(b"abc", 0x0B, 32));
+}
+
+#[test]
+fn test_sponge_params_rejects_invalid_values() {
+    let build = |params: SpongeParams| params.build().err();
+
+    assert_eq!(build(SpongeParams::new()), Some(SpongeParamsError::MissingCapacity));
+    assert_eq!(build(SpongeParams::new().rate(0)), Some(SpongeParamsError::InvalidRate(0)));
+    assert_eq!(build(SpongeParams::new().rate(200)), Some(SpongeParamsError::InvalidRate(200)));
+    assert_eq!(build(SpongeParams::new().capacity(0)), Some(SpongeParamsError::InvalidRate(200)));
+    assert_eq!(build(SpongeParams::new().capacity(1600)), Some(SpongeParamsError::InvalidRate(0)));
+    assert_eq!(build(SpongeParams::new().capacity(255)), Some(SpongeParamsError::CapacityNotByteAligned(255)));
+    assert_eq!(build(SpongeParams::new().security_level(800)), Some(SpongeParamsError::InvalidRate(0)));
+    assert_eq!(build(SpongeParams::new().rate(136).rounds(0)), Some(SpongeParamsError::InvalidRounds(0)));
+    assert_eq!(build(SpongeParams::new().rate(136).rounds(256)), Some(SpongeParamsError::InvalidRounds(256)));
+    assert_eq!(DomainSuffix::new(0x00), Err(SpongeParamsError::InvalidSuffix(0x00)));
+
+    // The last size given wins
+    assert_eq!(SpongeParams::new().rate(0).capacity(512).rate_bytes(), Ok(136));
+}
+
+#[test]
+fn test_domain_suffix_values() {
+    assert_eq!(DomainSuffix::SHA3.delimiter(), 0x06);
+    assert_eq!(u8::from(DomainSuffix::SHAKE), 0x1F);
+    assert_eq!(DomainSuffix::new(0x07), Ok(DomainSuffix::RAW_SHAKE));
+    assert_eq!(
+        SpongeParamsError::InvalidSuffix(0).to_string(),
+        "domain suffix 0x00 has no padding bit"
+    );
+}
@@ -49,9 +49,22 @@ pub fn lfsr_round_constant(round: usize) -> u64 {
 }
 
 /// Get round constant based on mode
+///
+/// Round indices beyond the table (used by Keccak-p with more than 24 rounds,
+/// see [`round_index`]) are always generated with the LFSR.
 pub fn get_round_constant(round: usize, mode: RoundConstantMode) -> u64 {
     match mode {
-        RoundConstantMode::Table => RC_TABLE[round],
-        RoundConstantMode::Lfsr => lfsr_round_constant(round),
+        RoundConstantMode::Table if round < RC_TABLE.len() => RC_TABLE[round],
+        _ => lfsr_round_constant(round),
     }
 }
+
+/// Round index i_r for the `i`-th of `rounds` rounds of Keccak-p[1600, rounds]
+///
+/// FIPS 202 runs i_r = 12 + 2l - nr .. 12 + 2l - 1 (l = 6 for 1600 bits), i.e. the
+/// last nr rounds of Keccak-f. For nr > 24 the first indices are negative; the
+/// round constant only depends on i_r mod 255 (the LFSR period), so the index is
+/// reduced into 0..255.
+pub fn round_index(i: usize, rounds: usize) -> usize {
+    (24 + i as i64 - rounds as i64).rem_euclid(255) as usize
+}
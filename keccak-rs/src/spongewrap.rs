@@ -0,0 +1,242 @@
+use alloc::{vec, vec::Vec};
+use crate::ct::{constant_time_eq, zeroize};
+use crate::duplex::Duplex;
+use crate::error::AuthenticationError;
+
+/// SpongeWrap runs on Keccak-f[1600] with a 256-bit capacity
+const WIDTH: usize = 1600;
+const RATE: usize = 168;
+
+/// Data block size rho: one byte of the rate is left for the frame bit and padding
+const BLOCK_SIZE: usize = RATE - 1;
+
+/// Length of the authentication tag appended to every ciphertext
+pub const SPONGEWRAP_TAG_LEN: usize = 16;
+
+/// Frame bit 0 (output of this call is discarded or is the tag), as a delimiter
+const FRAME_0: u8 = 0x02;
+/// Frame bit 1 (output of this call is keystream for the next message block)
+const FRAME_1: u8 = 0x03;
+
+/// SpongeWrap authenticated encryption (Bertoni, Daemen, Peeters, Van Assche,
+/// "Duplexing the sponge", SAC 2011) on a Keccak-f[1600] duplex object.
+///
+/// Every block fed to the duplex object is followed by a frame bit telling
+/// whether the next output is keystream (1) or not (0), so the key, the
+/// nonce, the associated data and the message can never be confused with one
+/// another. The key and the nonce each occupy one block (at most 167 bytes).
+///
+/// An instance is a session: consecutive `encrypt` calls authenticate
+/// everything wrapped before them, and the receiver must `decrypt` the same
+/// sequence in order. After a failed `decrypt` the session is out of sync with
+/// the sender, and any further call panics.
+#[derive(Clone)]
+pub struct SpongeWrap {
+    duplex: Duplex,
+    failed: bool,
+}
+
+impl SpongeWrap {
+    pub fn new(key: &[u8], nonce: &[u8]) -> Self {
+        assert!(!key.is_empty() && key.len() <= BLOCK_SIZE, "SpongeWrap key must be 1 to 167 bytes");
+        assert!(nonce.len() <= BLOCK_SIZE, "SpongeWrap nonce must be at most 167 bytes");
+
+        let mut duplex = Duplex::new(WIDTH, RATE);
+        duplex.duplexing_delimited(key, FRAME_0, &mut []);
+        duplex.duplexing_delimited(nonce, FRAME_0, &mut []);
+        SpongeWrap { duplex, failed: false }
+    }
+
+    /// Encrypts `plaintext` and authenticates it together with `associated_data`.
+    /// Returns the ciphertext followed by a [`SPONGEWRAP_TAG_LEN`]-byte tag.
+    pub fn encrypt(&mut self, associated_data: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        assert!(!self.failed, "SpongeWrap session was closed by a failed decryption");
+
+        let mut output = vec![0u8; plaintext.len() + SPONGEWRAP_TAG_LEN];
+        let (ciphertext, tag) = output.split_at_mut(plaintext.len());
+        self.wrap(associated_data, plaintext, ciphertext, tag, false);
+        output
+    }
+
+    /// Decrypts a ciphertext produced by [`encrypt`](Self::encrypt). On failure
+    /// no plaintext is released and the session refuses any further use.
+    pub fn decrypt(&mut self, associated_data: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, AuthenticationError> {
+        assert!(!self.failed, "SpongeWrap session was closed by a failed decryption");
+        if ciphertext.len() < SPONGEWRAP_TAG_LEN {
+            self.failed = true;
+            return Err(AuthenticationError);
+        }
+        let (ciphertext, received_tag) = ciphertext.split_at(ciphertext.len() - SPONGEWRAP_TAG_LEN);
+
+        let mut plaintext = vec![0u8; ciphertext.len()];
+        let mut tag = [0u8; SPONGEWRAP_TAG_LEN];
+        self.wrap(associated_data, ciphertext, &mut plaintext, &mut tag, true);
+
+        if constant_time_eq(&tag, received_tag) {
+            Ok(plaintext)
+        } else {
+            zeroize(&mut plaintext);
+            self.failed = true;
+            Err(AuthenticationError)
+        }
+    }
+
+    /// Shared body of wrap and unwrap: XORs `input` with the keystream into `output`
+    /// and writes the tag. The blocks fed back into the duplex object are always
+    /// the plaintext ones.
+    fn wrap(&mut self, associated_data: &[u8], input: &[u8], output: &mut [u8], tag: &mut [u8], decrypting: bool) {
+        // Header: every block but the last is followed by frame bit 0, the last by 1
+        let mut header = associated_data.chunks(BLOCK_SIZE).peekable();
+        let mut last_header: &[u8] = &[];
+        while let Some(block) = header.next() {
+            if header.peek().is_some() {
+                self.duplex.duplexing_delimited(block, FRAME_0, &mut []);
+            } else {
+                last_header = block;
+            }
+        }
+
+        let first_len = core::cmp::min(input.len(), BLOCK_SIZE);
+        let mut keystream = [0u8; BLOCK_SIZE];
+        self.duplex.duplexing_delimited(last_header, FRAME_1, &mut keystream[..first_len]);
+
+        // Body: at least one (possibly empty) block; frame bit 1 until the last block
+        let mut offset = 0;
+        loop {
+            let len = core::cmp::min(input.len() - offset, BLOCK_SIZE);
+            let mut plain_block = [0u8; BLOCK_SIZE];
+            for i in 0..len {
+                output[offset + i] = input[offset + i] ^ keystream[i];
+                plain_block[i] = if decrypting { output[offset + i] } else { input[offset + i] };
+            }
+            offset += len;
+
+            if offset == input.len() {
+                self.duplex.duplexing_delimited(&plain_block[..len], FRAME_0, tag);
+                zeroize(&mut plain_block);
+                zeroize(&mut keystream);
+                return;
+            }
+            let next_len = core::cmp::min(input.len() - offset, BLOCK_SIZE);
+            self.duplex.duplexing_delimited(&plain_block[..len], FRAME_1, &mut keystream[..next_len]);
+            zeroize(&mut plain_block);
+        }
+    }
+}
+
+// The AEAD modes of the crate share one test layout: round trips, tamper
+// detection through `assert_rejects_tampering`, session ordering, and
+// `*_regression_vectors` tests. The latter come from this implementation, not
+// from the designers' KAT files, and only pin the output against changes.
+
+/// Checks that `decrypt(key, nonce, associated_data, ciphertext)`, which must
+/// start a fresh session on every call, accepts the untouched inputs and rejects
+/// any single flipped bit, a changed key, nonce or associated data, and a
+/// ciphertext cut at either end or shorter than the tag.
+#[cfg(test)]
+pub(crate) fn assert_rejects_tampering(
+    key: &[u8],
+    nonce: &[u8],
+    associated_data: &[u8],
+    ciphertext: &[u8],
+    decrypt: impl Fn(&[u8], &[u8], &[u8], &[u8]) -> Result<Vec<u8>, AuthenticationError>,
+) {
+    let flipped = |data: &[u8], i: usize, bit: u8| {
+        let mut data = data.to_vec();
+        data[i] ^= bit;
+        data
+    };
+
+    assert!(decrypt(key, nonce, associated_data, ciphertext).is_ok());
+    for i in 0..ciphertext.len() {
+        for bit in 0..8 {
+            let tampered = flipped(ciphertext, i, 1 << bit);
+            assert!(decrypt(key, nonce, associated_data, &tampered).is_err(), "flipped byte {} bit {}", i, bit);
+        }
+    }
+    assert!(decrypt(&flipped(key, 0, 0x01), nonce, associated_data, ciphertext).is_err());
+    if !nonce.is_empty() {
+        assert!(decrypt(key, &flipped(nonce, nonce.len() - 1, 0x01), associated_data, ciphertext).is_err());
+    }
+    if !associated_data.is_empty() {
+        assert!(decrypt(key, nonce, &flipped(associated_data, 0, 0x20), ciphertext).is_err());
+        assert!(decrypt(key, nonce, b"", ciphertext).is_err());
+    }
+    assert!(decrypt(key, nonce, &[associated_data, b"!"].concat(), ciphertext).is_err());
+    assert!(decrypt(key, nonce, associated_data, &ciphertext[1..]).is_err());
+    assert!(decrypt(key, nonce, associated_data, &ciphertext[..ciphertext.len() - 1]).is_err());
+    assert!(decrypt(key, nonce, associated_data, &ciphertext[..4]).is_err());
+}
+
+#[cfg(test)]
+const TEST_KEY: &[u8] = b"SpongeWrap test key 0123456789ab";
+
+#[test]
+fn test_spongewrap_round_trip() {
+    for len in [0usize, 1, 166, 167, 168, 334, 1000] {
+        let plaintext: Vec<u8> = (0..len).map(|i| (i * 31 + 7) as u8).collect();
+        for ad_len in [0usize, 5, 167, 400] {
+            let ad = vec![0xADu8; ad_len];
+            let ciphertext = SpongeWrap::new(TEST_KEY, b"nonce").encrypt(&ad, &plaintext);
+            assert_eq!(ciphertext.len(), len + SPONGEWRAP_TAG_LEN);
+
+            let decrypted = SpongeWrap::new(TEST_KEY, b"nonce").decrypt(&ad, &ciphertext);
+            assert_eq!(decrypted, Ok(plaintext.clone()), "len = {}, ad_len = {}", len, ad_len);
+        }
+    }
+}
+
+#[test]
+fn test_spongewrap_tamper_detection() {
+    let ciphertext = SpongeWrap::new(TEST_KEY, b"nonce").encrypt(b"header", b"attack at dawn");
+    assert_rejects_tampering(TEST_KEY, b"nonce", b"header", &ciphertext, |key, nonce, ad, ct| {
+        SpongeWrap::new(key, nonce).decrypt(ad, ct)
+    });
+}
+
+#[test]
+fn test_spongewrap_associated_data_and_message_are_separated() {
+    // Moving bytes between the header and the body must change the tag
+    let a = SpongeWrap::new(TEST_KEY, b"nonce").encrypt(b"ab", b"");
+    let b = SpongeWrap::new(TEST_KEY, b"nonce").encrypt(b"", b"ab");
+    assert_ne!(a[a.len() - SPONGEWRAP_TAG_LEN..], b[b.len() - SPONGEWRAP_TAG_LEN..]);
+}
+
+#[test]
+fn test_spongewrap_session() {
+    let mut sender = SpongeWrap::new(TEST_KEY, b"session");
+    let first = sender.encrypt(b"", b"first message");
+    let second = sender.encrypt(b"seq 2", b"second message");
+
+    // Same message in a fresh session encrypts differently than in the running one
+    assert_ne!(SpongeWrap::new(TEST_KEY, b"session").encrypt(b"seq 2", b"second message"), second);
+
+    let mut receiver = SpongeWrap::new(TEST_KEY, b"session");
+    assert_eq!(receiver.decrypt(b"", &first).unwrap(), b"first message");
+    assert_eq!(receiver.decrypt(b"seq 2", &second).unwrap(), b"second message");
+
+    // Out-of-order delivery is rejected
+    let mut receiver = SpongeWrap::new(TEST_KEY, b"session");
+    assert!(receiver.decrypt(b"seq 2", &second).is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_spongewrap_session_closed_after_failure() {
+    let mut receiver = SpongeWrap::new(TEST_KEY, b"nonce");
+    assert!(receiver.decrypt(b"", &[0u8; SPONGEWRAP_TAG_LEN]).is_err());
+    receiver.encrypt(b"", b"must not be wrapped");
+}
+
+// SpongeWrap has no published byte-oriented test vectors
+
+#[test]
+fn test_spongewrap_regression_vectors() {
+    let ciphertext = SpongeWrap::new(TEST_KEY, b"").encrypt(b"", b"");
+    assert_eq!(hex::encode(ciphertext), "fd917af2fdeda0bd5c557cb2b8dee108");
+
+    let plaintext: Vec<u8> = (0..200u8).collect();
+    let ciphertext = SpongeWrap::new(TEST_KEY, b"nonce").encrypt(b"associated data", &plaintext);
+    assert_eq!(hex::encode(&ciphertext[..32]), "59ee09f8f4ecd2fd0ba7b8e2f3056ec1c8056ecdc48b32001be3498f9d1f0a14");
+    assert_eq!(hex::encode(&ciphertext[200..]), "44d39a35129873f58892d4ed182a50ea");
+}
@@ -0,0 +1,192 @@
+use alloc::{vec, vec::Vec};
+use crate::cshake::cshake_sponge;
+use crate::encoding::{encode_string, right_encode};
+use crate::{Keccak, XofReader};
+
+macro_rules! tuple_hash {
+    ($(#[$doc:meta])* $name:ident, $rate:expr) => {
+        $(#[$doc])*
+        ///
+        /// Every element is absorbed as `encode_string(element)`, so the boundaries
+        /// between elements are part of the hash: `["ab", "c"]` and `["a", "bc"]`
+        /// produce unrelated outputs.
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Keccak,
+        }
+
+        impl $name {
+            pub fn new(customization: &[u8]) -> Self {
+                $name {
+                    sponge: cshake_sponge($rate, b"TupleHash", customization),
+                }
+            }
+
+            pub fn push_element(&mut self, element: &[u8]) {
+                self.sponge.update(&encode_string(element));
+            }
+
+            pub fn finalize(mut self, output_len: usize) -> Vec<u8> {
+                self.sponge.update(&right_encode(output_len as u64 * 8));
+                self.sponge.finalize(output_len)
+            }
+
+            /// TupleHashXOF: output length is encoded as 0 and read on demand
+            pub fn finalize_xof(mut self) -> XofReader {
+                self.sponge.update(&right_encode(0));
+                self.sponge.finalize_xof()
+            }
+        }
+    };
+}
+
+tuple_hash!(
+    /// TupleHash128 (SP 800-185 section 5) over a sequence of byte strings
+    TupleHash128, 168
+);
+tuple_hash!(
+    /// TupleHash256 (SP 800-185 section 5) over a sequence of byte strings
+    TupleHash256, 136
+);
+
+pub fn tuple_hash128(elements: &[&[u8]], output_len: usize, customization: &[u8]) -> Vec<u8> {
+    let mut hasher = TupleHash128::new(customization);
+    for element in elements {
+        hasher.push_element(element);
+    }
+    hasher.finalize(output_len)
+}
+
+pub fn tuple_hash256(elements: &[&[u8]], output_len: usize, customization: &[u8]) -> Vec<u8> {
+    let mut hasher = TupleHash256::new(customization);
+    for element in elements {
+        hasher.push_element(element);
+    }
+    hasher.finalize(output_len)
+}
+
+pub fn tuple_hash_xof128(elements: &[&[u8]], output_len: usize, customization: &[u8]) -> Vec<u8> {
+    let mut hasher = TupleHash128::new(customization);
+    for element in elements {
+        hasher.push_element(element);
+    }
+    let mut output = vec![0u8; output_len];
+    hasher.finalize_xof().read(&mut output);
+    output
+}
+
+pub fn tuple_hash_xof256(elements: &[&[u8]], output_len: usize, customization: &[u8]) -> Vec<u8> {
+    let mut hasher = TupleHash256::new(customization);
+    for element in elements {
+        hasher.push_element(element);
+    }
+    let mut output = vec![0u8; output_len];
+    hasher.finalize_xof().read(&mut output);
+    output
+}
+
+// TupleHash samples from the NIST SP 800-185 example values
+
+#[cfg(test)]
+const SAMPLE_ELEMENTS: [&[u8]; 3] = [
+    &[0x00, 0x01, 0x02],
+    &[0x10, 0x11, 0x12, 0x13, 0x14, 0x15],
+    &[0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28],
+];
+
+#[test]
+fn test_tuple_hash128_samples() {
+    let out = tuple_hash128(&SAMPLE_ELEMENTS[..2], 32, b"");
+    assert_eq!(
+        hex::encode(out),
+        "c5d8786c1afb9b82111ab34b65b2c0048fa64e6d48e263264ce1707d3ffc8ed1"
+    );
+    let out = tuple_hash128(&SAMPLE_ELEMENTS[..2], 32, b"My Tuple App");
+    assert_eq!(
+        hex::encode(out),
+        "75cdb20ff4db1154e841d758e24160c54bae86eb8c13e7f5f40eb35588e96dfb"
+    );
+    let out = tuple_hash128(&SAMPLE_ELEMENTS, 32, b"My Tuple App");
+    assert_eq!(
+        hex::encode(out),
+        "e60f202c89a2631eda8d4c588ca5fd07f39e5151998deccf973adb3804bb6e84"
+    );
+}
+
+#[test]
+fn test_tuple_hash256_samples() {
+    let out = tuple_hash256(&SAMPLE_ELEMENTS[..2], 64, b"");
+    assert_eq!(
+        hex::encode(out),
+        "cfb7058caca5e668f81a12a20a2195ce97a925f1dba3e7449a56f82201ec607311ac2696b1ab5ea2352df1423bde7bd4bb78c9aed1a853c78672f9eb23bbe194"
+    );
+    let out = tuple_hash256(&SAMPLE_ELEMENTS[..2], 64, b"My Tuple App");
+    assert_eq!(
+        hex::encode(out),
+        "147c2191d5ed7efd98dbd96d7ab5a11692576f5fe2a5065f3e33de6bba9f3aa1c4e9a068a289c61c95aab30aee1e410b0b607de3620e24a4e3bf9852a1d4367e"
+    );
+    let out = tuple_hash256(&SAMPLE_ELEMENTS, 64, b"My Tuple App");
+    assert_eq!(
+        hex::encode(out),
+        "45000be63f9b6bfd89f54717670f69a9bc763591a4f05c50d68891a744bcc6e7d6d5b5e82c018da999ed35b0bb49c9678e526abd8e85c13ed254021db9e790ce"
+    );
+}
+
+#[test]
+fn test_tuple_hash_xof128_samples() {
+    let out = tuple_hash_xof128(&SAMPLE_ELEMENTS[..2], 32, b"");
+    assert_eq!(
+        hex::encode(out),
+        "2f103cd7c32320353495c68de1a8129245c6325f6f2a3d608d92179c96e68488"
+    );
+    let out = tuple_hash_xof128(&SAMPLE_ELEMENTS[..2], 32, b"My Tuple App");
+    assert_eq!(
+        hex::encode(out),
+        "3fc8ad69453128292859a18b6c67d7ad85f01b32815e22ce839c49ec374e9b9a"
+    );
+    let out = tuple_hash_xof128(&SAMPLE_ELEMENTS, 32, b"My Tuple App");
+    assert_eq!(
+        hex::encode(out),
+        "900fe16cad098d28e74d632ed852f99daab7f7df4d99e775657885b4bf76d6f8"
+    );
+}
+
+#[test]
+fn test_tuple_hash_xof256_samples() {
+    let out = tuple_hash_xof256(&SAMPLE_ELEMENTS[..2], 64, b"");
+    assert_eq!(
+        hex::encode(out),
+        "03ded4610ed6450a1e3f8bc44951d14fbc384ab0efe57b000df6b6df5aae7cd568e77377daf13f37ec75cf5fc598b6841d51dd207c991cd45d210ba60ac52eb9"
+    );
+    let out = tuple_hash_xof256(&SAMPLE_ELEMENTS[..2], 64, b"My Tuple App");
+    assert_eq!(
+        hex::encode(out),
+        "6483cb3c9952eb20e830af4785851fc597ee3bf93bb7602c0ef6a65d741aeca7e63c3b128981aa05c6d27438c79d2754bb1b7191f125d6620fca12ce658b2442"
+    );
+    let out = tuple_hash_xof256(&SAMPLE_ELEMENTS, 64, b"My Tuple App");
+    assert_eq!(
+        hex::encode(out),
+        "0c59b11464f2336c34663ed51b2b950bec743610856f36c28d1d088d8a2446284dd09830a6a178dc752376199fae935d86cfdee5913d4922dfd369b66a53c897"
+    );
+}
+
+#[test]
+fn test_tuple_hash_push_element_matches_slice_api() {
+    let mut hasher = TupleHash256::new(b"My Tuple App");
+    for element in SAMPLE_ELEMENTS.iter() {
+        hasher.push_element(element);
+    }
+    assert_eq!(hasher.finalize(64), tuple_hash256(&SAMPLE_ELEMENTS, 64, b"My Tuple App"));
+}
+
+#[test]
+fn test_tuple_hash_is_unambiguous() {
+    let split_late = tuple_hash128(&[b"ab", b"c"], 32, b"");
+    let split_early = tuple_hash128(&[b"a", b"bc"], 32, b"");
+    let single = tuple_hash128(&[b"abc"], 32, b"");
+    let with_empty = tuple_hash128(&[b"abc", b""], 32, b"");
+
+    assert_ne!(split_late, split_early);
+    assert_ne!(split_late, single);
+    assert_ne!(single, with_empty);
+}
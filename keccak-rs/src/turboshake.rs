@@ -0,0 +1,180 @@
+use crate::{Keccak, XofReader};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// TurboSHAKE runs the sponge on Keccak-p[1600, 12], the last 12 rounds of Keccak-f[1600]
+const TURBOSHAKE_ROUNDS: usize = 12;
+
+/// Domain separation byte used when the caller has no specific one (RFC 9861)
+pub const TURBOSHAKE_DEFAULT_DOMAIN: u8 = 0x1F;
+
+macro_rules! turboshake {
+    ($(#[$doc:meta])* $name:ident, $rate:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Keccak,
+        }
+
+        impl $name {
+            /// `domain` is the domain separation byte D, which must lie in 0x01..=0x7F.
+            /// Like the SHA-3 suffixes it already carries the first padding bit.
+            pub fn new(domain: u8) -> Self {
+                assert!((0x01..=0x7F).contains(&domain), "TurboSHAKE domain byte must be in 0x01..=0x7F");
+                $name {
+                    sponge: Keccak::with_rounds($rate, domain, TURBOSHAKE_ROUNDS),
+                }
+            }
+
+            pub fn update(&mut self, input: &[u8]) {
+                self.sponge.update(input);
+            }
+
+            #[cfg(feature = "alloc")]
+            pub fn finalize(self, output_len: usize) -> Vec<u8> {
+                self.sponge.finalize(output_len)
+            }
+
+            pub fn finalize_into(self, output: &mut [u8]) {
+                self.sponge.finalize_into(output);
+            }
+
+            pub fn finalize_xof(self) -> XofReader {
+                self.sponge.finalize_xof()
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new(TURBOSHAKE_DEFAULT_DOMAIN)
+            }
+        }
+    };
+}
+
+turboshake!(
+    /// Incremental TurboSHAKE128 (RFC 9861, rate 168 bytes)
+    TurboShake128, 168
+);
+turboshake!(
+    /// Incremental TurboSHAKE256 (RFC 9861, rate 136 bytes)
+    TurboShake256, 136
+);
+
+#[cfg(feature = "alloc")]
+pub fn turboshake128(data: &[u8], domain: u8, output_len: usize) -> Vec<u8> {
+    let mut hasher = TurboShake128::new(domain);
+    hasher.update(data);
+    hasher.finalize(output_len)
+}
+
+#[cfg(feature = "alloc")]
+pub fn turboshake256(data: &[u8], domain: u8, output_len: usize) -> Vec<u8> {
+    let mut hasher = TurboShake256::new(domain);
+    hasher.update(data);
+    hasher.finalize(output_len)
+}
+
+// TurboSHAKE test vectors from RFC 9861, section 5
+
+/// ptn(n): the repeating 00 01 .. FA byte pattern used by the RFC test vectors
+#[cfg(all(test, feature = "alloc"))]
+pub(crate) fn ptn(n: usize) -> Vec<u8> {
+    (0..n).map(|i| (i % 251) as u8).collect()
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_turboshake128_empty() {
+    assert_eq!(
+        hex::encode(turboshake128(b"", 0x1F, 32)),
+        "1e415f1c5983aff2169217277d17bb538cd945a397ddec541f1ce41af2c1b74c"
+    );
+    let long = turboshake128(b"", 0x1F, 10032);
+    assert_eq!(
+        hex::encode(&long[10000..]),
+        "a3b9b0385900ce761f22aed548e754da10a5242d62e8c658e3f3a923a7555607"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_turboshake128_ptn() {
+    let expected = [
+        "55cedd6f60af7bb29a4042ae832ef3f58db7299f893ebb9247247d856958daa9",
+        "9c97d036a3bac819db70ede0ca554ec6e4c2a1a4ffbfd9ec269ca6a111161233",
+        "96c77c279e0126f7fc07c9b07f5cdae1e0be60bdbe10620040e75d7223a624d2",
+        "d4976eb56bcf118520582b709f73e1d6853e001fdaf80e1b13e0d0599d5fb372",
+    ];
+    for (i, expected) in expected.iter().enumerate() {
+        let data = ptn(17usize.pow(i as u32));
+        assert_eq!(hex::encode(turboshake128(&data, 0x1F, 32)), *expected, "ptn(17^{})", i);
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_turboshake128_domains() {
+    let cases: [(&[u8], u8, &str); 6] = [
+        (&[0xFF; 3], 0x01, "bf323f940494e88ee1c540fe660be8a0c93f43d15ec006998462fa994eed5dab"),
+        (&[0xFF], 0x06, "8ec9c66465ed0d4a6c35d13506718d687a25cb05c74cca1e42501abd83874a67"),
+        (&[0xFF; 3], 0x07, "b658576001cad9b1e5f399a9f77723bba05458042d68206f7252682dba3663ed"),
+        (&[0xFF; 7], 0x0B, "8deeaa1aec47ccee569f659c21dfa8e112db3cee37b18178b2acd805b799cc37"),
+        (&[0xFF], 0x30, "553122e2135e363c3292bed2c6421fa232bab03daa07c7d6636603286506325b"),
+        (&[0xFF; 3], 0x7F, "16274cc656d44cefd422395d0f9053bda6d28e122aba15c765e5ad0e6eaf26f9"),
+    ];
+    for (data, domain, expected) in cases {
+        assert_eq!(hex::encode(turboshake128(data, domain, 32)), expected, "D = 0x{:02x}", domain);
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_turboshake256_empty() {
+    assert_eq!(
+        hex::encode(turboshake256(b"", 0x1F, 64)),
+        "367a329dafea871c7802ec67f905ae13c57695dc2c6663c61035f59a18f8e7db11edc0e12e91ea60eb6b32df06dd7f002fbafabb6e13ec1cc20d995547600db0"
+    );
+    let long = turboshake256(b"", 0x1F, 10032);
+    assert_eq!(
+        hex::encode(&long[10000..]),
+        "abefa11630c661269249742685ec082f207265dccf2f43534e9c61ba0c9d1d75"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_turboshake256_ptn() {
+    let expected = [
+        "3e1712f928f8eaf1054632b2aa0a246ed8b0c378728f60bc970410155c28820e90cc90d8a3006aa2372c5c5ea176b0682bf22bae7467ac94f74d43d39b0482e2",
+        "b3bab0300e6a191fbe6137939835923578794ea54843f5011090fa2f3780a9e5cb22c59d78b40a0fbff9e672c0fbe0970bd2c845091c6044d687054da5d8e9c7",
+        "66b810db8e90780424c0847372fdc95710882fde31c6df75beb9d4cd9305cfcae35e7b83e8b7e6eb4b78605880116316fe2c078a09b94ad7b8213c0a738b65c0",
+        "c74ebc919a5b3b0dd1228185ba02d29ef442d69d3d4276a93efe0bf9a16a7dc0cd4eabadab8cd7a5edd96695f5d360abe09e2c6511a3ec397da3b76b9e1674fb",
+    ];
+    for (i, expected) in expected.iter().enumerate() {
+        let data = ptn(17usize.pow(i as u32));
+        assert_eq!(hex::encode(turboshake256(&data, 0x1F, 64)), *expected, "ptn(17^{})", i);
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_turboshake256_domains() {
+    let cases: [(&[u8], u8, &str); 6] = [
+        (&[0xFF; 3], 0x01, "d21c6fbbf587fa2282f29aea620175fb0257413af78a0b1b2a87419ce031d933ae7a4d383327a8a17641a34f8a1d1003ad7da6b72dba84bb62fef28f62f12424"),
+        (&[0xFF], 0x06, "738d7b4e37d18b7f22ad1b5313e357e3dd7d07056a26a303c433fa3533455280f4f5a7d4f700efb437fe6d281405e07be32a0a972e22e63adc1b090daefe004b"),
+        (&[0xFF; 3], 0x07, "18b3b5b7061c2e67c1753a00e6ad7ed7ba1c906cf93efb7092eaf27fbeebb755ae6e292493c110e48d260028492b8e09b5500612b8f2578985ded5357d00ec67"),
+        (&[0xFF; 7], 0x0B, "bb36764951ec97e9d85f7ee9a67a7718fc005cf42556be79ce12c0bde50e5736d6632b0d0dfb202d1bbb8ffe3dd74cb00834fa756cb03471bab13a1e2c16b3c0"),
+        (&[0xFF], 0x30, "f3fe12873d34bcbb2e608779d6b70e7f86bec7e90bf113cbd4fdd0c4e2f4625e148dd7ee1a52776cf77f240514d9ccfc3b5ddab8ee255e39ee389072962c111a"),
+        (&[0xFF; 3], 0x7F, "abe569c1f77ec340f02705e7d37c9ab7e155516e4a6a150021d70b6fac0bb40c069f9a9828a0d575cd99f9bae435ab1acf7ed9110ba97ce0388d074bac768776"),
+    ];
+    for (data, domain, expected) in cases {
+        assert_eq!(hex::encode(turboshake256(data, domain, 64)), expected, "D = 0x{:02x}", domain);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_turboshake_rejects_domain_0x80() {
+    TurboShake128::new(0x80);
+}